@@ -1,6 +1,9 @@
 use velox::AppBuilder;
 
 fn main() {
-    let app = AppBuilder::from_config(include_str!("./velox.conf.json").to_string()).build();
+    let app = AppBuilder::from_config(include_str!("./velox.conf.json").to_string())
+        .unwrap()
+        .version(env!("CARGO_PKG_VERSION"))
+        .build();
     app.run().unwrap();
 }
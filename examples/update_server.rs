@@ -0,0 +1,71 @@
+//! Local stand-in for an update endpoint.
+//!
+//! Packs an executable and optionally a directory of assets into an archive, signs
+//! the archive and the manifest with a new ed25519 key and serves them as release `version`:
+//!
+//! ```sh
+//! cargo run --example update_server -- path/to/new-binary 0.2.0 [path/to/dist]
+//! ```
+//!
+//! The executable keeps its file name in the archive, it must be named like the
+//! executable of the app. Put the printed endpoint and public key into the `updater`
+//! section of the config.
+
+use std::fs;
+use std::path::Path;
+
+use ed25519_dalek::{Signer, SigningKey};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tiny_http::{Header, Response, Server};
+
+fn main() {
+    let usage = "usage: update_server <executable> <version> [dist]";
+    let mut args = std::env::args().skip(1);
+    let file = args.next().expect(usage);
+    let version = args.next().expect(usage);
+    let dist = args.next();
+
+    let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let name = Path::new(&file).file_name().expect(usage);
+    archive
+        .append_file(name, &mut fs::File::open(&file).unwrap())
+        .unwrap();
+    if let Some(dist) = &dist {
+        archive.append_dir_all("dist", dist).unwrap();
+    }
+    let archive = archive.into_inner().unwrap().finish().unwrap();
+
+    let key = SigningKey::from_bytes(&rand::random());
+
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", server.server_addr());
+    let manifest = serde_json::json!({
+        "version": version,
+        "notes": format!("Release {} served from {}", version, file),
+        "url": format!("{}/download", url),
+        "signature": base64::encode(key.sign(&archive).to_bytes()),
+    })
+    .to_string();
+    let signed = serde_json::json!({
+        "signature": base64::encode(key.sign(manifest.as_bytes()).to_bytes()),
+        "manifest": manifest,
+    });
+
+    println!("endpoint: {}/manifest.json", url);
+    println!("pubkey: {}", base64::encode(key.verifying_key().to_bytes()));
+
+    for req in server.incoming_requests() {
+        let res = match req.url() {
+            "/manifest.json" => req.respond(
+                Response::from_string(signed.to_string())
+                    .with_header("Content-Type: application/json".parse::<Header>().unwrap()),
+            ),
+            "/download" => req.respond(Response::from_data(archive.clone())),
+            _ => req.respond(Response::from_string("not found").with_status_code(404)),
+        };
+        if let Err(err) = res {
+            println!("{:?}", err.to_string());
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! App API for what concerns the whole app instead of a window.
+
+use crate::events::{Event, VeloxEvents};
+use crate::Result;
+
+use wry::application::event_loop::EventLoopProxy;
+
+/// Exits the app, for example from the menu of the tray while all windows are hidden.
+pub fn exit(event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::VeloxEvent(VeloxEvents::Exit))?;
+    Ok(true)
+}
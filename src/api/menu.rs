@@ -0,0 +1,65 @@
+//! Menu API that lets an app change the menu bars of its windows at runtime.
+
+use crate::events::{Event, WindowEvents};
+use crate::menu::{self, MenuItem, MenuItemUpdate};
+use crate::Result;
+
+use wry::application::event_loop::EventLoopProxy;
+
+/// Replaces the menu bar of a window, an empty menu removes it.
+/// Fails if the menu contains other items than submenus.
+pub fn set_menu(
+    menu: Vec<MenuItem>,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    menu::check_menu_bar(&menu)?;
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::SetMenu {
+        menu,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+/// Changes the title, state or check mark of a menu item.
+pub fn update_item(
+    id: String,
+    update: MenuItemUpdate,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::UpdateMenuItem {
+        id,
+        update,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+/// Enables or disables a menu item.
+pub fn set_enabled(
+    id: String,
+    flag: bool,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    let update = MenuItemUpdate {
+        enabled: Some(flag),
+        ..MenuItemUpdate::default()
+    };
+    update_item(id, update, window_identifier, event_proxy)
+}
+
+/// Checks or unchecks a check item.
+pub fn set_checked(
+    id: String,
+    flag: bool,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    let update = MenuItemUpdate {
+        checked: Some(flag),
+        ..MenuItemUpdate::default()
+    };
+    update_item(id, update, window_identifier, event_proxy)
+}
@@ -1,7 +1,13 @@
 //! API for velox that can be used to interact with native window or filesystem.
 //! These API can also be called from javascript using "__VELOX__" object.
 
+pub mod app;
 pub mod fs;
+pub mod menu;
 pub mod notification;
+pub mod splashscreen;
+pub mod store;
 pub mod subprocess;
+pub mod tray;
+pub mod updater;
 pub mod window;
@@ -0,0 +1,27 @@
+//! Splashscreen API that lets an app report its loading progress and
+//! decide itself when it is ready.
+
+use crate::events::{Event, VeloxEvents};
+use crate::Result;
+
+use wry::application::event_loop::EventLoopProxy;
+
+/// Shows the loading progress of the app in the splashscreen.
+/// `percent` goes from 0 to 100, `text` describes what is being loaded.
+pub fn set_progress(
+    percent: f64,
+    text: Option<String>,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::VeloxEvent(VeloxEvents::SplashscreenProgress {
+        percent,
+        text,
+    }))?;
+    Ok(true)
+}
+
+/// Closes the splashscreen and shows the main window.
+pub fn close(event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::VeloxEvent(VeloxEvents::SplashscreenClose))?;
+    Ok(true)
+}
@@ -0,0 +1,89 @@
+//! Store API that lets rust read and change the persistent key-value stores of an app.
+//! Changes are emitted to all windows just like changes from javascript.
+//!
+//! Every function returns a receiver for the result.
+//! Note- Don't block on the receiver in the thread which runs the event loop.
+
+use crate::events::{Event, Reply, VeloxEvents};
+use crate::store::StoreAction;
+use crate::Result;
+
+use crossbeam_channel::Receiver;
+use wry::application::event_loop::EventLoopProxy;
+
+/// Reads a value, null if the key does not exist.
+pub fn get(
+    store: &str,
+    key: &str,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<Receiver<Result<wry::Value>>> {
+    send(
+        store,
+        StoreAction::Get {
+            key: key.to_string(),
+        },
+        event_proxy,
+    )
+}
+
+/// Sets a value and saves the store.
+pub fn set(
+    store: &str,
+    key: &str,
+    value: wry::Value,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<Receiver<Result<wry::Value>>> {
+    send(
+        store,
+        StoreAction::Set {
+            key: key.to_string(),
+            value,
+        },
+        event_proxy,
+    )
+}
+
+/// Deletes a value, resolves with whether the key existed.
+pub fn delete(
+    store: &str,
+    key: &str,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<Receiver<Result<wry::Value>>> {
+    send(
+        store,
+        StoreAction::Delete {
+            key: key.to_string(),
+        },
+        event_proxy,
+    )
+}
+
+/// Lists the keys of a store.
+pub fn keys(
+    store: &str,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<Receiver<Result<wry::Value>>> {
+    send(store, StoreAction::Keys, event_proxy)
+}
+
+/// Deletes all values of a store.
+pub fn clear(
+    store: &str,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<Receiver<Result<wry::Value>>> {
+    send(store, StoreAction::Clear, event_proxy)
+}
+
+fn send(
+    store: &str,
+    action: StoreAction,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<Receiver<Result<wry::Value>>> {
+    let (reply, receiver) = Reply::channel();
+    event_proxy.send_event(Event::VeloxEvent(VeloxEvents::Store {
+        store: store.to_string(),
+        action,
+        reply: Some(reply),
+    }))?;
+    Ok(receiver)
+}
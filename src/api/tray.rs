@@ -0,0 +1,43 @@
+//! Tray API that lets an app change its tray at runtime, see `tray`.
+
+use crate::events::{Event, VeloxEvents};
+use crate::menu::{MenuItem, MenuItemUpdate};
+use crate::tray::TrayAction;
+use crate::Result;
+
+use wry::application::event_loop::EventLoopProxy;
+
+/// Changes the tray, errors are logged since the tray is changed on the event loop.
+pub fn apply(action: TrayAction, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::VeloxEvent(VeloxEvents::Tray(action)))?;
+    Ok(true)
+}
+
+/// Changes the icon to a PNG or ICO file in the asset directory.
+pub fn set_icon(icon: String, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    apply(TrayAction::SetIcon(icon), event_proxy)
+}
+
+/// Changes the text which is shown when the mouse hovers over the tray.
+pub fn set_tooltip(tooltip: Option<String>, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    apply(TrayAction::SetTooltip(tooltip), event_proxy)
+}
+
+/// Shows or hides the tray.
+pub fn set_visible(flag: bool, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    apply(TrayAction::SetVisible(flag), event_proxy)
+}
+
+/// Replaces the menu of the tray, an empty menu removes it.
+pub fn set_menu(menu: Vec<MenuItem>, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    apply(TrayAction::SetMenu(menu), event_proxy)
+}
+
+/// Changes the title, state or check mark of a menu item.
+pub fn update_item(
+    id: String,
+    update: MenuItemUpdate,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    apply(TrayAction::UpdateMenuItem { id, update }, event_proxy)
+}
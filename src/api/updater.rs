@@ -0,0 +1,19 @@
+//! Updater API that lets an app check for and install updates from rust.
+//! The updater plugin reports every stage as `updater-status` event to all windows.
+
+use crate::events::{Event, VeloxEvents};
+use crate::Result;
+
+use wry::application::event_loop::EventLoopProxy;
+
+/// Looks for a newer release of the app.
+pub fn check(event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::VeloxEvent(VeloxEvents::CheckUpdate))?;
+    Ok(true)
+}
+
+/// Downloads and installs the newest release, then restarts the app.
+pub fn install(event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::VeloxEvent(VeloxEvents::InstallUpdate))?;
+    Ok(true)
+}
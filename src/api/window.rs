@@ -1,16 +1,43 @@
 //! Window API that can be used for interacting with tao window.
 //! For example: changing title, changing width or height, etc.
 
-use crate::events::{Event, WindowEvents};
-use crate::Result;
+use crate::app::ContentType;
+use crate::events::{Event, Reply, WindowEvents};
+use crate::window::{ResizeDirection, WindowOptions};
+use crate::{Error, Result};
 
+use crossbeam_channel::Receiver;
 use wry::application::event_loop::EventLoopProxy;
 
-pub fn add_window(title: String, url: String, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+/// Opens a new window. `reply` receives `true` once the window was created,
+/// or the result of a modal window when it closes.
+pub fn add_window(
+    title: String,
+    content: ContentType,
+    options: WindowOptions,
+    reply: Option<Reply>,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
     event_proxy.send_event(Event::WindowEvent(WindowEvents::AddWindow {
         window_title: title.clone(),
-        content: url,
+        content,
         identifier: title,
+        options: Box::new(options),
+        reply,
+    }))?;
+    Ok(true)
+}
+
+/// Opens a window which is declared in the config file.
+/// `reply` receives `true` once the window was created.
+pub fn open_window(
+    window_identifier: String,
+    reply: Option<Reply>,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::OpenWindow {
+        identifier: window_identifier,
+        reply,
     }))?;
     Ok(true)
 }
@@ -58,6 +85,236 @@ pub fn minimize(
     Ok(true)
 }
 
+/// Moves a window with the mouse until the left mouse button is released.
+pub fn start_drag(window_identifier: String, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::StartDrag(
+        window_identifier,
+    )))?;
+    Ok(true)
+}
+
+/// Resizes a window with the mouse until the left mouse button is released.
+pub fn start_resize(
+    direction: ResizeDirection,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::StartResize {
+        direction,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+/// Reloads the page of a window.
+pub fn reload(window_identifier: String, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::Reload(window_identifier)))?;
+    Ok(true)
+}
+
+/// Loads a url in a window.
+pub fn navigate(
+    url: String,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::Navigate {
+        url,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+/// Goes back in the history of a window.
+pub fn go_back(window_identifier: String, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::GoBack(window_identifier)))?;
+    Ok(true)
+}
+
+/// Goes forward in the history of a window.
+pub fn go_forward(window_identifier: String, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::GoForward(
+        window_identifier,
+    )))?;
+    Ok(true)
+}
+
+/// Sets the zoom factor of a window, `1.0` is the default.
+pub fn set_zoom(
+    factor: f64,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    if !factor.is_finite() || factor <= 0.0 {
+        return Err(Error::CommandError {
+            detail: format!("Invalid zoom factor {}", factor),
+        });
+    }
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::SetZoom {
+        factor,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+/// Evaluates a script in a window. Can be called from any thread.
+/// `reply` receives the JSON-serialized result of the script.
+pub fn eval(
+    script: String,
+    window_identifier: String,
+    reply: Option<Reply>,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::Eval {
+        script,
+        identifier: window_identifier,
+        reply,
+    }))?;
+    Ok(true)
+}
+
+/// Evaluates a script in a window and returns a receiver for the JSON-serialized result
+/// of the script. Promises are awaited before their value is sent.
+/// Note- Don't block on the receiver in the thread which runs the event loop.
+pub fn eval_with_result(
+    script: String,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<Receiver<Result<wry::Value>>> {
+    let (reply, receiver) = Reply::channel();
+    eval(script, window_identifier, Some(reply), event_proxy)?;
+    Ok(receiver)
+}
+
+/// Delivers the result of a script which was evaluated with a reply.
+pub fn eval_result(
+    window_identifier: String,
+    token: String,
+    success: bool,
+    value: wry::Value,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::EvalResult {
+        identifier: window_identifier,
+        token,
+        success,
+        value,
+    }))?;
+    Ok(true)
+}
+
+/// Sets the icon of a window, `path` is a PNG or ICO file in the asset directory.
+pub fn set_icon(
+    path: String,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::SetIcon {
+        path,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+/// Opens the web inspector of a window, only works if the app is in debug mode.
+pub fn open_devtools(
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::OpenDevtools(
+        window_identifier,
+    )))?;
+    Ok(true)
+}
+
+/// Sets the result which a modal window returns to its opener.
+pub fn set_result(
+    result: wry::Value,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::SetResult {
+        result,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+pub fn intercept_close(
+    flag: bool,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::InterceptClose {
+        flag,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+pub fn confirm_close(
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::ConfirmClose(
+        window_identifier,
+    )))?;
+    Ok(true)
+}
+
+/// Requests identifiers of all open windows, which are delivered to `reply`.
+pub fn get_windows(reply: Reply, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::GetWindows(reply)))?;
+    Ok(true)
+}
+
+/// Requests size, position and state of a window, which are delivered to `reply`.
+pub fn get_window_info(
+    window_identifier: String,
+    reply: Reply,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::GetWindowInfo {
+        identifier: window_identifier,
+        reply,
+    }))?;
+    Ok(true)
+}
+
+/// Requests all monitors connected to the system, which are delivered to `reply`.
+pub fn get_monitors(reply: Reply, event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::GetMonitors(reply)))?;
+    Ok(true)
+}
+
+pub fn set_monitor(
+    monitor: usize,
+    window_identifier: String,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::SetMonitor {
+        monitor,
+        identifier: window_identifier,
+    }))?;
+    Ok(true)
+}
+
+/// Sends a message from window `source` to window `target`.
+/// The message is broadcast to all other windows if no target is given.
+pub fn post_message(
+    source: String,
+    target: Option<String>,
+    payload: wry::Value,
+    event_proxy: EventLoopProxy<Event>,
+) -> Result<bool> {
+    event_proxy.send_event(Event::WindowEvent(WindowEvents::PostMessage {
+        source,
+        target,
+        payload,
+    }))?;
+    Ok(true)
+}
+
 // pub fn minimize(proxy: Arc<WindowProxy>) -> Result<()> {
 //     proxy.minimize()?;
 //     Ok(())
@@ -1,25 +1,82 @@
-use crate::handler::call_func;
-use crate::window::WebviewWindow;
-use crate::{config, events, plugin, server, Error, Result};
+use crate::cli::{self, ArgMatches};
+use crate::config::{SplashscreenConfig, TrayConfig, UpdaterConfig, WindowConfig};
+use crate::crash::CrashReport;
+use crate::events::Reply;
+use crate::handler::{call_deferred, call_func};
+use crate::menu::{self, MenuItem};
+use crate::plugin::single_instance::SingleInstance;
+use crate::plugin::splashscreen::Splashscreen;
+use crate::plugin::store::StorePlugin;
+use crate::plugin::updater::UpdaterPlugin;
+use crate::plugin::{dynamic, Plugin, PluginHandle};
+use crate::tray::{Tray, TrayAction, TrayEvent};
+use crate::window::{MonitorInfo, WebviewWindow, WindowOptions};
+use crate::{
+    assets, config, convert_to_json, crash, events, json, logger, server, Error, Response, Result,
+};
 
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+use serde::{Deserialize, Serialize};
 use wry::{
     application::{
-        event_loop::{ControlFlow, EventLoop, EventLoopProxy},
-        window::{Window, WindowBuilder, WindowId},
+        event::WindowEvent,
+        event_loop::{ControlFlow, EventLoop, EventLoopProxy, EventLoopWindowTarget},
+        window::{Theme, Window, WindowBuilder, WindowId},
     },
-    webview::WebViewBuilder,
+    webview::{RpcRequest, RpcResponse, WebViewBuilder},
 };
 
 pub type InvokeHandler = Arc<
     Mutex<dyn FnMut(EventLoopProxy<events::Event>, Request) -> Option<wry::Value> + Send + Sync>,
 >;
 
+/// Called with the identifier of a window when the user requests to close it.
+/// Returning `false` keeps the window open.
+pub type CloseRequestedHandler = Arc<Mutex<dyn FnMut(&str) -> bool + Send + Sync>>;
+
 /// Describes type of content that will be displayed on a webview window
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub enum ContentType {
+    /// An absolute url
     Url(String),
+    /// Inline html
     Html(String),
+    /// A path relative to the directory of the bundled assets
+    Asset(String),
+}
+
+impl ContentType {
+    /// Returns the url that is loaded by the webview.
+    /// `base_url` is the url of the server which hosts the frontend.
+    pub fn to_url(&self, base_url: &str) -> String {
+        match self {
+            ContentType::Url(url) => url.clone(),
+            // base64 keeps `#` and `%` of the html from being read as parts of the url
+            ContentType::Html(html) => {
+                "data:text/html;charset=utf-8;base64,".to_string() + &base64::encode(html)
+            }
+            ContentType::Asset(path) => format!(
+                "{}/{}",
+                base_url.trim_end_matches('/'),
+                path.trim_start_matches('/')
+            ),
+        }
+    }
+}
+
+/// Describes when the app exits after a window was closed
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ExitPolicy {
+    /// Exit when the main window is closed
+    #[default]
+    OnMainWindowClose,
+    /// Exit when no window is left open
+    OnLastWindowClose,
+    /// Keep running when windows are closed, useful for apps that live in the system tray
+    Never,
 }
 
 /// The application runner.
@@ -33,15 +90,43 @@ pub struct App {
     pub invoke_handler: Option<InvokeHandler>,
     /// Url of the local server where frontend is hosted
     pub url: String,
-    /// Content to display while app is still loading
-    pub splashscreen: Option<String>,
+    /// Hook that can veto closing a window.
+    pub close_requested_handler: Option<CloseRequestedHandler>,
+    /// When the app exits after a window was closed
+    pub exit_policy: ExitPolicy,
+    /// Windows that are declared in the config file
+    pub windows: Vec<WindowConfig>,
+    /// Whether keyboard shortcuts change the zoom of windows
+    pub zoom_hotkeys: bool,
+    /// Icon of all windows, relative to `asset_dir`
+    pub icon: Option<String>,
+    /// Menu bar of the main window
+    pub menu: Option<Vec<MenuItem>>,
+    /// Icon of the app in the system tray
+    pub tray: Option<TrayConfig>,
+    /// Directory of the bundled assets
+    pub asset_dir: String,
+    /// Plugins of the app
+    pub plugins: Vec<PluginHandle>,
+    /// Command-line arguments which were parsed with the `args` schema of the config file
+    pub matches: ArgMatches,
+    /// Version of the app, from the `args` section of the config file or `AppBuilder::version`
+    pub version: Option<String>,
+    /// Crash report of the previous run if it crashed, see `crash::previous_crash`
+    pub previous_crash: Option<CrashReport>,
 }
 
 pub struct Application {
     /// Event loop of the application
     pub event_loop: Option<EventLoop<events::Event>>,
+    /// Proxy for sending events to the event loop
+    pub event_loop_proxy: EventLoopProxy<events::Event>,
     /// Webview windows
     pub webviews: Vec<WebviewWindow>,
+    /// The app which is being run
+    pub app: App,
+    /// Icon of the app in the system tray
+    pub tray: Option<Tray>,
 }
 
 /// Describes an incoming request from javascript.
@@ -59,10 +144,13 @@ pub enum Request {
 }
 
 impl Application {
-    pub fn new(event_loop: Option<EventLoop<events::Event>>) -> Self {
+    pub fn new(event_loop: EventLoop<events::Event>, app: App) -> Self {
         Self {
-            event_loop,
+            event_loop_proxy: event_loop.create_proxy(),
+            event_loop: Some(event_loop),
             webviews: vec![],
+            app,
+            tray: None,
         }
     }
 
@@ -70,40 +158,518 @@ impl Application {
         self.webviews.push(window_identifier);
     }
 
+    /// Builds a new webview window which can talk to velox and adds it to the app.
+    pub fn create_window(
+        &mut self,
+        event_loop_target: &EventLoopWindowTarget<events::Event>,
+        window_builder: WindowBuilder,
+        content: &str,
+        identifier: String,
+    ) -> Result<()> {
+        // windows are looked up by their identifier, which must be unique
+        if self.window_index(&identifier).is_some() {
+            return Err(Error::CommandError {
+                detail: format!("A window with identifier `{}` is already open", identifier),
+            });
+        }
+
+        let visible = window_builder.window.visible;
+        let window = window_builder
+            .build(event_loop_target)
+            .map_err(wry::Error::from)?;
+
+        let webview = WebViewBuilder::new(window)?
+            .with_url(content)?
+            .with_rpc_handler(rpc_handler(
+                self.app.clone(),
+                self.event_loop_proxy.clone(),
+                identifier.clone(),
+            ))
+            .with_initialization_script(&init_script(&identifier, &self.app))
+            .build()?;
+
+        let window = WebviewWindow::new(identifier, webview, visible);
+        window.set_devtools(self.app.debug);
+        window.watch_load_failures(self.event_loop_proxy.clone());
+
+        for plugin in &self.app.plugins {
+            plugin.lock().unwrap().window_created(&window);
+        }
+
+        self.add_window(window);
+        Ok(())
+    }
+
+    /// Adds an icon to a window builder. Falls back to the icon of the app if
+    /// `path` is `None`, errors are logged and leave the default icon.
+    pub fn with_icon(&self, window_builder: WindowBuilder, path: Option<&str>) -> WindowBuilder {
+        match path.or(self.app.icon.as_deref()) {
+            Some(path) => match assets::load_icon(&self.app.asset_dir, path) {
+                Ok(icon) => window_builder.with_window_icon(Some(icon)),
+                Err(err) => {
+                    log::error!("{}", err);
+                    window_builder
+                }
+            },
+            None => window_builder,
+        }
+    }
+
+    /// Opens a window from javascript or rust with the given options.
+    /// `reply` receives `true` once the window was created, or the result of a
+    /// modal window when it closes.
+    pub fn open_window(
+        &mut self,
+        event_loop_target: &EventLoopWindowTarget<events::Event>,
+        window_builder: WindowBuilder,
+        content: ContentType,
+        identifier: String,
+        options: WindowOptions,
+        reply: Option<Reply>,
+    ) {
+        let url = content.to_url(&self.app.url);
+        let window_builder = self.with_icon(window_builder, options.icon.as_deref());
+
+        if let Err(err) = self.create_window(event_loop_target, window_builder, &url, identifier) {
+            match reply {
+                Some(reply) => self.reply(reply, Err(err)),
+                None => log::error!("{}", err),
+            }
+            return;
+        }
+
+        let index = self.webviews.len() - 1;
+        let parent = options.parent.as_ref().and_then(|p| self.window_index(p));
+
+        if let Some(menu) = options.menu.clone() {
+            if let Err(err) = self.webviews[index].set_menu(menu, self.event_loop_proxy.clone()) {
+                log::error!("{}", err);
+            }
+        }
+
+        if let Some(parent) = parent {
+            self.webviews[index].set_parent(&self.webviews[parent]);
+            self.webviews[index].parent = options.parent.clone();
+
+            if options.modal {
+                self.webviews[index].modal = true;
+                self.webviews[parent].set_enabled(false);
+            }
+        }
+
+        match reply {
+            Some(reply) if options.modal && parent.is_some() => {
+                self.webviews[index].modal_reply = Some(reply);
+            }
+            Some(reply) => self.reply(reply, Ok(json!(true))),
+            None => {}
+        }
+    }
+
+    /// Opens a window which is declared in the config file.
+    pub fn open_configured_window(
+        &mut self,
+        event_loop_target: &EventLoopWindowTarget<events::Event>,
+        window_identifier: &str,
+        reply: Option<Reply>,
+    ) {
+        let window = self
+            .app
+            .windows
+            .iter()
+            .find(|window| window.identifier == window_identifier)
+            .cloned();
+
+        match window {
+            Some(window) => {
+                let window_builder = window.window_builder(&self.app.name);
+                let content = window
+                    .content
+                    .unwrap_or_else(|| ContentType::Url(self.app.url.clone()));
+                let options = WindowOptions {
+                    icon: window.icon,
+                    menu: window.menu,
+                    ..WindowOptions::default()
+                };
+
+                self.open_window(
+                    event_loop_target,
+                    window_builder,
+                    content,
+                    window.identifier,
+                    options,
+                    reply,
+                );
+            }
+            None => {
+                if let Some(reply) = reply {
+                    let detail = format!("No window with identifier `{}`", window_identifier);
+                    self.reply(reply, Err(Error::CommandError { detail }));
+                }
+            }
+        }
+    }
+
+    /// Returns the index of a window with the given identifier.
+    pub fn window_index(&self, window_identifier: &str) -> Option<usize> {
+        self.webviews
+            .iter()
+            .position(|item| item.identifier == window_identifier)
+    }
+
+    /// Returns the index of a window with the given window id.
+    pub fn window_index_by_id(&self, window_id: WindowId) -> Option<usize> {
+        self.webviews
+            .iter()
+            .position(|item| item.window_id == window_id)
+    }
+
     pub fn remove_window(
         &mut self,
         window_identifier: Option<String>,
         window_id: Option<WindowId>,
     ) {
-        if let Some(iden) = window_identifier {
-            let index = self
-                .webviews
-                .iter()
-                .position(|item| item.identifier.contains(&iden))
-                .unwrap();
-            self.webviews.remove(index);
+        let index = if let Some(iden) = window_identifier {
+            self.window_index(&iden)
         } else if let Some(id) = window_id {
-            let index = self
-                .webviews
-                .iter()
-                .position(|item| item.window_id == id)
-                .unwrap();
+            self.window_index_by_id(id)
+        } else {
+            None
+        };
+
+        if let Some(index) = index {
             self.webviews.remove(index);
         }
     }
 
     pub fn show_window(&mut self, window_identifier: String) {
-        let index = self
+        if let Some(index) = self.window_index(&window_identifier) {
+            self.webviews[index].set_visible(true);
+        }
+    }
+
+    pub fn hide_window(&mut self, window_identifier: String) {
+        if let Some(index) = self.window_index(&window_identifier) {
+            self.webviews[index].set_visible(false);
+        }
+    }
+
+    /// Delivers the result of a deferred request.
+    pub fn reply(&self, reply: Reply, result: Result<wry::Value>) {
+        match reply {
+            Reply::Rpc { window, id } => {
+                let script = match result {
+                    Ok(value) => RpcResponse::get_result_script(id, convert_to_json(value)),
+                    Err(err) => {
+                        RpcResponse::get_error_script(id, Response::from_error(err.to_string()))
+                    }
+                };
+
+                if let (Some(index), Ok(script)) = (self.window_index(&window), script) {
+                    if let Err(err) = self.webviews[index].eval(&script) {
+                        log::error!("{}", err);
+                    }
+                }
+            }
+            Reply::Channel(sender) => {
+                if let Err(err) = sender.send(result) {
+                    log::error!("{}", err);
+                }
+            }
+        }
+    }
+
+    /// Fails the pending scripts of a window whose page is left, their results never arrive.
+    fn cancel_evals(&mut self, index: usize) {
+        let identifier = self.webviews[index].identifier.clone();
+        let replies: Vec<Reply> = self.webviews[index]
+            .pending_evals
+            .drain()
+            .map(|(_, reply)| reply)
+            .collect();
+
+        for reply in replies {
+            self.reply(
+                reply,
+                Err(Error::ScriptError {
+                    detail: format!("The page of window `{}` was left", identifier),
+                }),
+            );
+        }
+    }
+
+    /// Evaluates a script in a window. If `reply` is given, it receives the
+    /// JSON-serialized result of the script.
+    fn eval(&mut self, identifier: &str, script: &str, reply: Option<Reply>) {
+        let index = match self.window_index(identifier) {
+            Some(index) => index,
+            None => {
+                let detail = format!("No window with identifier `{}`", identifier);
+                match reply {
+                    Some(reply) => self.reply(reply, Err(Error::CommandError { detail })),
+                    None => log::error!("{}", detail),
+                }
+                return;
+            }
+        };
+
+        let res = match reply.clone() {
+            Some(reply) => self.webviews[index].eval_with_result(script, reply),
+            None => self.webviews[index].eval(script),
+        };
+
+        if let Err(err) = res {
+            match reply {
+                Some(reply) => self.reply(reply, Err(err)),
+                None => log::error!("{}", err),
+            }
+        }
+    }
+
+    /// Tells rust and javascript about a later launch of the app and focuses the main window.
+    fn second_instance(&mut self, args: &[String], cwd: &str, event: events::Event) {
+        let payload = json!({ "args": args, "cwd": cwd });
+        for window in &self.webviews {
+            if let Err(err) = window.emit("second-instance", payload.clone()) {
+                log::error!("{}", err);
+            }
+        }
+
+        if let Some(index) = self.window_index("main_window") {
+            self.webviews[index].focus();
+        }
+
+        self.app
+            .run_invoke_handler(self.event_loop_proxy.clone(), Request::Event(event));
+    }
+
+    /// Emits the activation of a menu item to the javascript of its window and to rust.
+    fn menu_activated(
+        &mut self,
+        window: &str,
+        id: &str,
+        checked: Option<bool>,
+        event: events::Event,
+    ) {
+        if let Some(index) = self.window_index(window) {
+            let webview = &mut self.webviews[index];
+            // gtk toggles check items itself
+            if let (Some(checked), Some(item)) =
+                (checked, menu::find_item_mut(&mut webview.menu, id))
+            {
+                item.checked = checked;
+            }
+
+            if let Err(err) = webview.emit("menu", json!({ "id": id, "checked": checked })) {
+                log::error!("{}", err);
+            }
+        }
+
+        self.app
+            .run_invoke_handler(self.event_loop_proxy.clone(), Request::Event(event));
+    }
+
+    /// Changes the tray, errors are logged.
+    fn tray_action(&mut self, action: TrayAction) {
+        let result = match &mut self.tray {
+            Some(tray) => tray.apply(action, &self.app.asset_dir),
+            None => Err(Error::TrayError {
+                detail: "The app has no tray".to_string(),
+            }),
+        };
+        if let Err(err) = result {
+            log::error!("{}", err);
+        }
+    }
+
+    /// Emits a click on the tray or an activated item of its menu to the javascript of all
+    /// windows and to rust. A click shows the main window if it was hidden to the tray.
+    fn tray_event(&mut self, tray_event: &TrayEvent, event: events::Event) {
+        match tray_event {
+            TrayEvent::MenuActivated {
+                id,
+                checked: Some(checked),
+            } => {
+                if let Some(tray) = &mut self.tray {
+                    tray.set_checked(id, *checked);
+                }
+            }
+            TrayEvent::Click if self.hides_to_tray() => {
+                if let Some(index) = self.window_index("main_window") {
+                    self.webviews[index].focus();
+                }
+            }
+            _ => {}
+        }
+
+        let payload = json!(tray_event);
+        for window in &self.webviews {
+            if let Err(err) = window.emit("tray", payload.clone()) {
+                log::error!("{}", err);
+            }
+        }
+
+        self.app
+            .run_invoke_handler(self.event_loop_proxy.clone(), Request::Event(event));
+    }
+
+    /// Whether closing the main window hides it to the tray. Only linux emits the clicks
+    /// which show it again.
+    fn hides_to_tray(&self) -> bool {
+        cfg!(target_os = "linux")
+            && self.tray.is_some()
+            && self
+                .app
+                .tray
+                .as_ref()
+                .is_some_and(|tray| tray.hide_on_close)
+    }
+
+    /// Closes a window which the user wants to close, the main window is hidden instead
+    /// if it hides to the tray.
+    fn close_requested_window(&mut self, index: usize, control_flow: &mut ControlFlow) {
+        if self.webviews[index].identifier == "main_window" && self.hides_to_tray() {
+            self.webviews[index].set_visible(false);
+        } else {
+            self.close_window(index, control_flow);
+        }
+    }
+
+    /// Asks the rust hook and javascript whether a window should be closed.
+    fn request_close(&mut self, index: usize, control_flow: &mut ControlFlow) {
+        let identifier = self.webviews[index].identifier.clone();
+
+        if let Some(handler) = &self.app.close_requested_handler {
+            if !handler.lock().unwrap()(&identifier) {
+                return;
+            }
+        }
+
+        if self.webviews[index].intercept_close {
+            // javascript confirms the close request through `confirm_close`
+            let script = format!(
+                r#"window.__VELOX__.event._dispatch("close-requested", {payload}).then(function (prevented) {{
+                    if (!prevented) window.__VELOX__.rpc.call("confirm_close", {identifier});
+                }})"#,
+                payload = json!({ "identifier": identifier }),
+                identifier = wry::Value::from(identifier.as_str()),
+            );
+            if let Err(err) = self.webviews[index].eval(&script) {
+                log::error!("{}", err);
+            }
+        } else {
+            self.close_requested_window(index, control_flow);
+        }
+    }
+
+    /// Closes a window and exits the app if required by the exit policy.
+    fn close_window(&mut self, index: usize, control_flow: &mut ControlFlow) {
+        let mut window = self.webviews.remove(index);
+
+        #[cfg(not(target_os = "linux"))]
+        menu::release_tao_items(&menu::MenuSource::Window(window.identifier.clone()));
+
+        for (_, reply) in window.pending_evals.drain() {
+            self.reply(
+                reply,
+                Err(Error::ScriptError {
+                    detail: format!("Window `{}` was closed", window.identifier),
+                }),
+            );
+        }
+
+        // child windows close with their parent
+        while let Some(child) = self
             .webviews
             .iter()
-            .position(|item| item.identifier.contains(&window_identifier))
-            .unwrap();
-        self.webviews[index].webview.window().set_visible(true);
+            .position(|item| item.parent.as_ref() == Some(&window.identifier))
+        {
+            self.close_window(child, control_flow);
+        }
+
+        // the parent stays disabled while another of its modal windows is open
+        if window.modal {
+            if let Some(parent) = window.parent.as_ref().and_then(|p| self.window_index(p)) {
+                let modals = self
+                    .webviews
+                    .iter()
+                    .filter(|item| item.modal && item.parent == window.parent)
+                    .count();
+                if modals == 0 {
+                    self.webviews[parent].set_enabled(true);
+                }
+            }
+        }
+
+        if let Some(reply) = window.modal_reply.clone() {
+            self.reply(reply, Ok(window.result.clone()));
+        }
+
+        let exit = match self.app.exit_policy {
+            ExitPolicy::OnMainWindowClose => window.identifier == "main_window",
+            ExitPolicy::OnLastWindowClose => self.webviews.is_empty(),
+            ExitPolicy::Never => false,
+        };
+
+        if exit {
+            *control_flow = ControlFlow::Exit;
+        }
+    }
+
+    /// Forwards native window events to the webview which owns the window.
+    fn handle_window_event(
+        &mut self,
+        index: usize,
+        event: WindowEvent,
+        control_flow: &mut ControlFlow,
+    ) {
+        let res = match event {
+            WindowEvent::CloseRequested => {
+                self.request_close(index, control_flow);
+                Ok(())
+            }
+            WindowEvent::Resized(size) => self.webviews[index].emit(
+                "resized",
+                json!({ "width": size.width, "height": size.height }),
+            ),
+            WindowEvent::Moved(position) => {
+                self.webviews[index].emit("moved", json!({ "x": position.x, "y": position.y }))
+            }
+            WindowEvent::Focused(focused) => {
+                self.webviews[index].focused = focused;
+                self.webviews[index].emit("focused", json!({ "focused": focused }))
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => self.webviews[index].emit(
+                "scale-factor-changed",
+                json!({
+                    "scaleFactor": scale_factor,
+                    "width": new_inner_size.width,
+                    "height": new_inner_size.height,
+                }),
+            ),
+            WindowEvent::ThemeChanged(theme) => {
+                let theme = match theme {
+                    Theme::Light => "light",
+                    Theme::Dark => "dark",
+                };
+                self.webviews[index].emit("theme-changed", json!({ "theme": theme }))
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(err) = res {
+            log::error!("{}", err);
+        }
     }
 
     // Runs event loop of the app and responds to valid events
     pub fn run(mut self) {
-        use wry::application::event::{Event, StartCause, WindowEvent};
+        use wry::application::event::{Event, StartCause};
+        #[cfg(not(target_os = "linux"))]
+        use wry::application::menu::MenuType;
 
         let event_loop = self.event_loop.take().unwrap();
 
@@ -113,98 +679,396 @@ impl Application {
             match event {
                 Event::NewEvents(StartCause::Init) => {}
                 Event::WindowEvent {
-                    window_id,
-                    event: WindowEvent::CloseRequested,
-                    ..
+                    window_id, event, ..
                 } => {
-                    // self.remove_webview(None, Some(window_id));
-                    if window_id == self.webviews[0].window_id {
-                        *control_flow = ControlFlow::Exit;
+                    if let Some(index) = self.window_index_by_id(window_id) {
+                        self.handle_window_event(index, event, control_flow);
                     }
                 }
 
                 Event::UserEvent(user_event) => {
                     use events::WindowEvents;
 
+                    for plugin in &self.app.plugins {
+                        plugin.lock().unwrap().event_received(None, &user_event);
+                    }
+
+                    if let events::Event::VeloxEvent(events::VeloxEvents::SecondInstance {
+                        args,
+                        cwd,
+                    }) = &user_event
+                    {
+                        self.second_instance(args, cwd, user_event.clone());
+                    }
+                    if let events::Event::VeloxEvent(events::VeloxEvents::MenuActivated {
+                        window,
+                        id,
+                        checked,
+                    }) = &user_event
+                    {
+                        self.menu_activated(window, id, *checked, user_event.clone());
+                    }
+                    if let events::Event::VeloxEvent(events::VeloxEvents::Tray(action)) =
+                        &user_event
+                    {
+                        self.tray_action(action.clone());
+                    }
+                    if let events::Event::VeloxEvent(events::VeloxEvents::TrayEvent(tray_event)) =
+                        &user_event
+                    {
+                        self.tray_event(tray_event, user_event.clone());
+                    }
+                    if let events::Event::VeloxEvent(events::VeloxEvents::Exit) = &user_event {
+                        *control_flow = ControlFlow::Exit;
+                    }
+
                     match user_event {
                         events::Event::WindowEvent(WindowEvents::AddWindow {
                             window_title,
                             content,
                             identifier,
+                            options,
+                            reply,
                         }) => {
-                            let splash_window = WindowBuilder::new()
-                                .with_title(window_title)
-                                .build(&event_loop_target)
-                                .unwrap();
-
-                            let id = splash_window.id();
+                            let window_builder =
+                                options.apply(WindowBuilder::new().with_title(window_title));
 
-                            let webview = WebViewBuilder::new(splash_window)
-                                .unwrap()
-                                .with_url(&content)
-                                .unwrap()
-                                .build()
-                                .unwrap();
-
-                            let window_identifier = WebviewWindow {
+                            self.open_window(
+                                event_loop_target,
+                                window_builder,
+                                content,
                                 identifier,
-                                window_id: id,
-                                webview,
-                            };
+                                *options,
+                                reply,
+                            );
+                        }
 
-                            self.add_window(window_identifier);
+                        events::Event::WindowEvent(WindowEvents::OpenWindow {
+                            identifier,
+                            reply,
+                        }) => {
+                            self.open_configured_window(event_loop_target, &identifier, reply);
                         }
 
                         events::Event::WindowEvent(WindowEvents::ShowWindow(id)) => {
                             self.show_window(id);
                         }
 
+                        events::Event::WindowEvent(WindowEvents::HideWindow(id)) => {
+                            self.hide_window(id);
+                        }
+
                         events::Event::WindowEvent(WindowEvents::CloseWindow(id)) => {
-                            self.remove_window(Some(id), None);
+                            if let Some(index) = self.window_index(&id) {
+                                self.close_window(index, control_flow);
+                            }
                         }
 
                         events::Event::WindowEvent(WindowEvents::SetFullscreen { identifier }) => {
-                            let index = self
-                                .webviews
-                                .iter()
-                                .position(|item| item.identifier.contains(&identifier))
-                                .unwrap();
-                            self.webviews[index].fullscreen();
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.webviews[index].fullscreen();
+                            }
                         }
 
                         events::Event::WindowEvent(WindowEvents::SetTitle {
                             title,
                             identifier,
                         }) => {
-                            let index = self
-                                .webviews
-                                .iter()
-                                .position(|item| item.identifier.contains(&identifier))
-                                .unwrap();
-                            self.webviews[index].set_title(title);
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.webviews[index].set_title(title);
+                            }
                         }
 
                         events::Event::WindowEvent(WindowEvents::Maximize { flag, identifier }) => {
-                            let index = self
-                                .webviews
-                                .iter()
-                                .position(|item| item.identifier.contains(&identifier))
-                                .unwrap();
-                            self.webviews[index].maximize(flag);
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.webviews[index].maximize(flag);
+                            }
                         }
 
                         events::Event::WindowEvent(WindowEvents::Minimize { flag, identifier }) => {
-                            let index = self
-                                .webviews
-                                .iter()
-                                .position(|item| item.identifier.contains(&identifier))
-                                .unwrap();
-                            self.webviews[index].minimize(flag);
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.webviews[index].minimize(flag);
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::StartDrag(identifier)) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                if let Err(err) = self.webviews[index].start_drag() {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::StartResize {
+                            direction,
+                            identifier,
+                        }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.webviews[index].start_resize(direction);
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::Reload(identifier)) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.cancel_evals(index);
+                                if let Err(err) = self.webviews[index].reload() {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::Navigate { url, identifier }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.cancel_evals(index);
+                                if let Err(err) = self.webviews[index].navigate(&url) {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        // pages which were loaded by links or scripts drop their pending scripts too
+                        events::Event::WindowEvent(WindowEvents::PageLoaded(identifier)) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.cancel_evals(index);
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::GoBack(identifier)) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.cancel_evals(index);
+                                if let Err(err) = self.webviews[index].go_back() {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::GoForward(identifier)) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.cancel_evals(index);
+                                if let Err(err) = self.webviews[index].go_forward() {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::SetMenu { menu, identifier }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                let proxy = self.event_loop_proxy.clone();
+                                if let Err(err) = self.webviews[index].set_menu(menu, proxy) {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::UpdateMenuItem {
+                            id,
+                            update,
+                            identifier,
+                        }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                if let Err(err) =
+                                    self.webviews[index].update_menu_item(&id, &update)
+                                {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::SetZoom {
+                            factor,
+                            identifier,
+                        }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                if let Err(err) = self.webviews[index].set_zoom(factor) {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::Eval {
+                            script,
+                            identifier,
+                            reply,
+                        }) => self.eval(&identifier, &script, reply),
+
+                        events::Event::WindowEvent(WindowEvents::EvalResult {
+                            identifier,
+                            token,
+                            success,
+                            value,
+                        }) => {
+                            let reply = self.window_index(&identifier).and_then(|index| {
+                                self.webviews[index].pending_evals.remove(&token)
+                            });
+
+                            if let Some(reply) = reply {
+                                let res = if success {
+                                    Ok(value)
+                                } else {
+                                    Err(Error::ScriptError {
+                                        detail: value.as_str().unwrap_or_default().to_string(),
+                                    })
+                                };
+                                self.reply(reply, res);
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::SetIcon { path, identifier }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                match assets::load_icon(&self.app.asset_dir, &path) {
+                                    Ok(icon) => self.webviews[index].set_icon(icon),
+                                    Err(err) => log::error!("{}", err),
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::OpenDevtools(identifier)) => {
+                            if let (true, Some(index)) =
+                                (self.app.debug, self.window_index(&identifier))
+                            {
+                                self.webviews[index].open_devtools();
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::SetResult {
+                            result,
+                            identifier,
+                        }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.webviews[index].result = result;
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::InterceptClose {
+                            flag,
+                            identifier,
+                        }) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.webviews[index].intercept_close = flag;
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::ConfirmClose(identifier)) => {
+                            if let Some(index) = self.window_index(&identifier) {
+                                self.close_requested_window(index, control_flow);
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::GetWindows(reply)) => {
+                            let identifiers: Vec<&String> =
+                                self.webviews.iter().map(|item| &item.identifier).collect();
+                            self.reply(reply, Ok(json!(identifiers)));
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::GetWindowInfo {
+                            identifier,
+                            reply,
+                        }) => {
+                            let res = match self.window_index(&identifier) {
+                                Some(index) => Ok(json!(self.webviews[index].info())),
+                                None => Err(Error::CommandError {
+                                    detail: format!("No window with identifier `{}`", identifier),
+                                }),
+                            };
+                            self.reply(reply, res);
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::GetMonitors(reply)) => {
+                            let monitors: Vec<MonitorInfo> = event_loop_target
+                                .available_monitors()
+                                .map(|monitor| MonitorInfo::from(&monitor))
+                                .collect();
+                            self.reply(reply, Ok(json!(monitors)));
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::PostMessage {
+                            source,
+                            target,
+                            payload,
+                        }) => {
+                            let message = json!({ "source": source, "payload": payload });
+
+                            for window in self.webviews.iter().filter(|item| match &target {
+                                Some(target) => &item.identifier == target,
+                                None => item.identifier != source,
+                            }) {
+                                if let Err(err) = window.emit("message", message.clone()) {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::Emit {
+                            event,
+                            payload,
+                            target,
+                        }) => {
+                            for window in self.webviews.iter().filter(|item| match &target {
+                                Some(target) => &item.identifier == target,
+                                None => true,
+                            }) {
+                                if let Err(err) = window.emit(&event, payload.clone()) {
+                                    log::error!("{}", err);
+                                }
+                            }
+                        }
+
+                        events::Event::WindowEvent(WindowEvents::SetMonitor {
+                            monitor,
+                            identifier,
+                        }) => {
+                            let monitor = event_loop_target.available_monitors().nth(monitor);
+                            if let (Some(index), Some(monitor)) =
+                                (self.window_index(&identifier), monitor)
+                            {
+                                self.webviews[index].set_monitor(&monitor);
+                            }
                         }
 
                         _ => {}
                     }
                 }
+
+                // linux builds its menus with gtk, which sends `VeloxEvents::MenuActivated`
+                // and `VeloxEvents::TrayEvent`
+                #[cfg(not(target_os = "linux"))]
+                Event::MenuEvent {
+                    menu_id,
+                    origin: MenuType::SystemTray,
+                } => {
+                    let activated = self
+                        .tray
+                        .as_mut()
+                        .and_then(|tray| tray.menu_activated(menu_id.0));
+                    if let Some((id, checked)) = activated {
+                        let event = menu::MenuSource::Tray.activated(id, checked);
+                        if let Err(err) = self.event_loop_proxy.send_event(event) {
+                            log::error!("{}", err);
+                        }
+                    }
+                }
+
+                #[cfg(not(target_os = "linux"))]
+                Event::MenuEvent { menu_id, .. } => {
+                    let activated = self.webviews.iter_mut().find_map(|window| {
+                        let identifier = window.identifier.clone();
+                        window
+                            .menu_activated(menu_id.0)
+                            .map(|(id, checked)| (identifier, id, checked))
+                    });
+                    if let Some((window, id, checked)) = activated {
+                        let event = menu::MenuSource::Window(window).activated(id, checked);
+                        if let Err(err) = self.event_loop_proxy.send_event(event) {
+                            log::error!("{}", err);
+                        }
+                    }
+                }
+
+                Event::LoopDestroyed => {
+                    for plugin in &self.app.plugins {
+                        plugin.lock().unwrap().exit();
+                    }
+                }
                 _ => (),
             }
         });
@@ -221,6 +1085,27 @@ impl App {
         Ok(())
     }
 
+    /// Runs a command of a plugin, `method` has the form `plugin:<name>|<command>`.
+    /// Returns `None` if no plugin with that name is registered.
+    pub fn run_plugin_command(
+        &self,
+        method: &str,
+        params: &[wry::Value],
+    ) -> Option<Result<wry::Value>> {
+        let (name, command) = method.strip_prefix("plugin:")?.split_once('|')?;
+        let plugin = self
+            .plugins
+            .iter()
+            .find(|plugin| plugin.lock().unwrap().name() == name)?;
+
+        let res = plugin.lock().unwrap().invoke(command, params);
+        Some(res.unwrap_or_else(|| {
+            Err(Error::CommandError {
+                detail: format!("Plugin `{}` has no command `{}`", name, command),
+            })
+        }))
+    }
+
     /// Runs the invoke handler if defined.
     /// Returns whether the message was consumed or not.
     /// The message is considered consumed if the handler exists and returns an Ok Result.
@@ -245,51 +1130,134 @@ pub struct AppBuilder {
     pub invoke_handler: Option<InvokeHandler>,
     /// Url of the local server where frontend is hosted
     pub url: String,
-    pub splashscreen: Option<String>,
+    pub splashscreen: Option<SplashscreenConfig>,
+    pub plugins: Vec<PluginHandle>,
+    /// Directory with plugins which are loaded from shared libraries
+    pub plugin_dir: Option<String>,
+    /// Whether later launches of the app are forwarded to the running instance
+    pub single_instance: bool,
+    /// Socket of the single instance which was claimed before the asset server started
+    instance: Option<SingleInstance>,
+    /// Command-line arguments which were parsed with the `args` schema of the config file
+    pub matches: ArgMatches,
+    /// Version of the app, from the `args` section of the config file or `AppBuilder::version`
+    pub version: Option<String>,
+    /// Crash report of the previous run if it crashed
+    pub previous_crash: Option<CrashReport>,
+    /// Where the app looks for updates
+    pub updater: Option<UpdaterConfig>,
+    /// Hook that can veto closing a window.
+    pub close_requested_handler: Option<CloseRequestedHandler>,
+    pub exit_policy: ExitPolicy,
+    /// Windows that are declared in the config file
+    pub windows: Vec<WindowConfig>,
+    pub zoom_hotkeys: bool,
+    pub icon: Option<String>,
+    /// Menu bar of the main window
+    pub menu: Option<Vec<MenuItem>>,
+    pub tray: Option<TrayConfig>,
+    /// Directory of the bundled assets
+    pub asset_dir: String,
 }
 
 impl AppBuilder {
     /// Creates a new App builder from a valid velox-config file
-    pub fn from_config(config: String) -> Self {
+    pub fn from_config(config: String) -> Result<Self> {
         use portpicker::pick_unused_port;
 
-        let config = config::parse_config(&config).unwrap(); // Parses the velox config file
+        let config = config::parse_config(&config)?; // Parses the velox config file
+
+        if let Err(err) = logger::init(&config.name, &config.log) {
+            eprintln!("could not initialise logger: {}", err);
+        }
+        crash::install(&config.name);
+        let previous_crash = crash::previous_crash(&config.name);
+        if let Some(report) = &previous_crash {
+            log::warn!(
+                "previous run crashed at {}: {}",
+                report.time,
+                report.message
+            );
+        }
+
+        // exits after printing `--help` or `--version`. Cargo.toml is only found while
+        // developing, so it is not used as version of the app.
+        let version = config.args.version.clone();
+        let cli_version = version.clone().or_else(|| {
+            config::parse_cargo_config()
+                .ok()
+                .map(|cargo| cargo.package.version)
+        });
+        let matches = cli::parse_args(
+            &config.name,
+            &config.description,
+            cli_version.as_deref(),
+            &config.args,
+        );
+
+        // exit before a second asset server is started
+        let instance = if config.single_instance {
+            match SingleInstance::claim(&config.name) {
+                Some(instance) => Some(instance),
+                None => std::process::exit(0),
+            }
+        } else {
+            None
+        };
 
         let arg = std::env::args().find(|arg| arg.contains("target")); // To find whether this is an packaged app or not
 
         // If this is not a packaged app, then serve assets from a user defined url.
         // Else start a new local server and serve bundled assets
-        if let Some(_arg) = arg {
-            Self {
-                name: config.name,
-                debug: config.debug,
-                invoke_handler: None,
-                url: config.dev_server_url,
-                splashscreen: None,
-            }
+        let (url, asset_dir) = if let Some(_arg) = arg {
+            (config.dev_server_url.clone(), config.build_dir.clone())
         } else {
             let port = pick_unused_port().expect("no unused port");
             let url = format!("127.0.0.1:{}", port);
             server::spawn_server(&url, config.clone());
-            Self {
-                name: config.name,
-                debug: config.debug,
-                invoke_handler: None,
-                url: "http://".to_owned() + &url,
-                splashscreen: None,
-            }
-        }
+            (
+                "http://".to_owned() + &url,
+                assets::get_asset_path(&config)?,
+            )
+        };
+
+        Ok(Self {
+            name: config.name,
+            debug: config.debug,
+            invoke_handler: None,
+            url,
+            splashscreen: config
+                .splashscreen
+                .filter(|splashscreen| splashscreen.enable),
+            plugins: vec![],
+            plugin_dir: config.plugin_dir,
+            single_instance: config.single_instance,
+            instance,
+            matches,
+            version,
+            previous_crash,
+            updater: config.updater.filter(|updater| updater.active),
+            close_requested_handler: None,
+            exit_policy: ExitPolicy::default(),
+            windows: config.windows,
+            zoom_hotkeys: config.zoom_hotkeys,
+            icon: config.icon,
+            menu: config.menu,
+            tray: config.tray,
+            asset_dir,
+        })
     }
 
     /// show splashcreen with custom html
-    pub fn show_splashscreen(self, content: String) -> Self {
-        Self {
-            name: self.name,
-            debug: self.debug,
-            invoke_handler: self.invoke_handler,
-            url: self.url,
-            splashscreen: Some("data:text/html,".to_string() + &content),
-        }
+    pub fn show_splashscreen(mut self, content: String) -> Self {
+        self.splashscreen = Some(SplashscreenConfig::new(ContentType::Html(content)));
+        self
+    }
+
+    /// show splashscreen with a url, inline html or a bundled asset
+    pub fn splashscreen(mut self, splashscreen: SplashscreenConfig) -> Self {
+        self.splashscreen = Some(splashscreen);
+        self
     }
 
     /// Defines the JS message handler callback.
@@ -303,40 +1271,268 @@ impl AppBuilder {
         self
     }
 
+    /// Defines a hook which is called with the identifier of a window when the user
+    /// requests to close it. Returning `false` keeps the window open.
+    pub fn on_close_requested<F: FnMut(&str) -> bool + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.close_requested_handler = Some(Arc::new(Mutex::new(handler)));
+        self
+    }
+
+    /// Defines when the app exits after a window was closed.
+    pub fn exit_policy(mut self, exit_policy: ExitPolicy) -> Self {
+        self.exit_policy = exit_policy;
+        self
+    }
+
+    /// Enables Ctrl + Plus, Ctrl + Minus and Ctrl + 0 for zooming windows.
+    pub fn zoom_hotkeys(mut self, flag: bool) -> Self {
+        self.zoom_hotkeys = flag;
+        self
+    }
+
+    /// Sets the icon of all windows, a PNG or ICO file in the asset directory.
+    pub fn icon(mut self, path: String) -> Self {
+        self.icon = Some(path);
+        self
+    }
+
+    /// Sets the menu bar of the main window.
+    pub fn menu(mut self, menu: Vec<MenuItem>) -> Self {
+        self.menu = Some(menu);
+        self
+    }
+
+    /// Shows the app in the system tray.
+    pub fn tray(mut self, tray: TrayConfig) -> Self {
+        self.tray = Some(tray);
+        self
+    }
+
+    /// Registers a plugin.
+    pub fn plugin<P: Plugin + 'static>(mut self, plugin: P) -> Self {
+        self.plugins.push(Arc::new(Mutex::new(plugin)));
+        self
+    }
+
+    /// Loads every shared library in a directory as plugin when the app is built.
+    /// Relative directories are resolved against the directory of the executable.
+    pub fn plugin_dir(mut self, dir: String) -> Self {
+        self.plugin_dir = Some(dir);
+        self
+    }
+
+    /// Forwards later launches of the app to the running instance, which emits a
+    /// `second-instance` event and focuses its main window.
+    pub fn single_instance(mut self, flag: bool) -> Self {
+        self.single_instance = flag;
+        self
+    }
+
+    /// Sets the version of the app which is compared with new releases by the updater,
+    /// usually `env!("CARGO_PKG_VERSION")` of the app.
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    /// Requires the version of the app, see `version`, without it the updater is disabled.
+    /// Requires the version of the app, see `version`.
+    pub fn updater(mut self, updater: UpdaterConfig) -> Self {
+        self.updater = Some(updater);
+        self
+    }
+
     /// Builds the App Struct.
-    pub fn build(self) -> App {
+    pub fn build(mut self) -> App {
+        if let Some(splashscreen) = self.splashscreen {
+            self.plugins
+                .insert(0, Arc::new(Mutex::new(Splashscreen::new(splashscreen))));
+        }
+        self.plugins
+            .push(Arc::new(Mutex::new(StorePlugin::new(&self.name))));
+        if self.single_instance {
+            // `from_config` already claimed the socket unless it was enabled afterwards
+            let name = &self.name;
+            match self.instance.take().or_else(|| SingleInstance::claim(name)) {
+                Some(instance) => self.plugins.push(Arc::new(Mutex::new(instance))),
+                None => std::process::exit(0),
+            }
+        }
+        // relaunches after the single-instance socket was removed in the exit hooks
+        match (self.updater, &self.version) {
+            (Some(updater), Some(version)) => self.plugins.push(Arc::new(Mutex::new(
+                UpdaterPlugin::new(updater, version.clone()),
+            ))),
+            (Some(_), None) => log::error!(
+                "The updater is disabled, it needs the version of the app, see `AppBuilder::version`"
+            ),
+            _ => {}
+        }
+        if let Some(dir) = &self.plugin_dir {
+            for plugin in dynamic::load_plugins(Path::new(dir)) {
+                self.plugins.push(Arc::new(Mutex::new(plugin)));
+            }
+        }
+
         App {
             name: self.name,
-            debug: self.debug,
+            // debug tools are never shipped with release builds
+            debug: self.debug && cfg!(debug_assertions),
             invoke_handler: self.invoke_handler,
             url: self.url,
-            splashscreen: self.splashscreen,
+            close_requested_handler: self.close_requested_handler,
+            exit_policy: self.exit_policy,
+            windows: self.windows,
+            zoom_hotkeys: self.zoom_hotkeys,
+            icon: self.icon,
+            menu: self.menu,
+            tray: self.tray,
+            asset_dir: self.asset_dir,
+            plugins: self.plugins,
+            matches: self.matches,
+            version: self.version,
+            previous_crash: self.previous_crash,
         }
     }
 }
 
 ///Builds a webview instance with all the required details.
-pub fn build_webview(app_config: App) -> Result<Application> {
-    use crate::Response;
-    use crossbeam_channel::unbounded;
-    use wry::webview::{RpcRequest, RpcResponse};
+pub fn build_webview(mut app_config: App) -> Result<Application> {
+    let event_loop = EventLoop::<events::Event>::with_user_event();
 
-    let (sender, receiver) = unbounded();
+    for plugin in app_config.plugins.clone() {
+        plugin
+            .lock()
+            .unwrap()
+            .setup(&mut app_config, event_loop.create_proxy())?;
+    }
 
     let app_conf = app_config.clone();
 
-    let event_loop = EventLoop::<events::Event>::with_user_event();
+    let main_window = app_conf
+        .windows
+        .iter()
+        .find(|window| window.identifier == "main_window")
+        .cloned();
+    let window_builder = match &main_window {
+        Some(window) => window.window_builder(&app_conf.name),
+        None => WindowBuilder::new().with_title(&app_conf.name),
+    };
+
+    let mut app = Application::new(event_loop, app_config);
+    let event_loop = app.event_loop.take().unwrap();
 
-    let event_loop_proxy = event_loop.create_proxy();
+    let url = app.app.url.clone();
+    let window_builder = app.with_icon(
+        window_builder,
+        main_window
+            .as_ref()
+            .and_then(|window| window.icon.as_deref()),
+    );
+    app.create_window(&event_loop, window_builder, &url, "main_window".to_string())?;
 
-    let handler = move |_window: &Window, req: RpcRequest| {
-        let params = if let wry::Value::Array(params) = req.params.unwrap() {
+    let menu = main_window
+        .and_then(|window| window.menu)
+        .or_else(|| app.app.menu.clone());
+    if let Some(menu) = menu {
+        let proxy = app.event_loop_proxy.clone();
+        app.webviews.last_mut().unwrap().set_menu(menu, proxy)?;
+    }
+
+    if let Some(config) = app.app.tray.clone() {
+        if config.hide_on_close && !cfg!(target_os = "linux") {
+            log::warn!("hide_on_close of the tray is only supported on linux, it is ignored");
+        }
+        match config.icon.as_ref().or(app.app.icon.as_ref()) {
+            Some(icon) => {
+                let proxy = app.event_loop_proxy.clone();
+                let tray = assets::resolve(&app.app.asset_dir, icon)
+                    .and_then(|icon| Tray::new(&config, &icon, &event_loop, proxy));
+                match tray {
+                    Ok(tray) => app.tray = Some(tray),
+                    Err(err) => log::error!("{}", err),
+                }
+            }
+            None => log::error!("The tray needs an icon, neither the tray nor the app has one"),
+        }
+    }
+
+    // create windows which are declared in the config file
+    for window in app.app.windows.clone() {
+        if window.startup && window.identifier != "main_window" {
+            app.open_configured_window(&event_loop, &window.identifier, None);
+        }
+    }
+
+    app.event_loop = Some(event_loop);
+
+    Ok(app)
+}
+
+/// Creates the handler for JSON-RPC messages that are sent from a webview window.
+/// Events are also passed to the plugins of the app.
+fn rpc_handler(
+    app_config: App,
+    event_loop_proxy: EventLoopProxy<events::Event>,
+    window_identifier: String,
+) -> impl Fn(&Window, RpcRequest) -> Option<RpcResponse> + 'static {
+    move |_window: &Window, req: RpcRequest| {
+        let mut params = if let wry::Value::Array(params) = req.params.unwrap() {
             params.to_vec()
         } else {
             vec![]
         };
 
+        // the source of a message or script result is always the window which sent it
+        if req.method == "post_message" || req.method == "eval_result" {
+            params.insert(0, wry::Value::from(window_identifier.as_str()));
+        }
+
+        // console output of javascript is logged with the window identifier as target
+        if req.id.is_none() && req.method == "log" {
+            logger::log_from_js(&window_identifier, &params);
+            return None;
+        }
+
+        // params can hold user data, which release builds never write to the log file
+        if cfg!(debug_assertions) {
+            log::debug!(
+                "[{}] rpc request: {} {:?}",
+                window_identifier,
+                req.method,
+                params
+            );
+        }
+
         if let Some(id) = req.id {
+            let reply = Reply::Rpc {
+                window: window_identifier.clone(),
+                id: id.clone(),
+            };
+
+            if let Some(res) = app_config.run_plugin_command(&req.method, &params) {
+                return Some(match res {
+                    Ok(value) => RpcResponse::new_result(Some(id), Some(convert_to_json(value))),
+                    Err(err) => {
+                        let res = Response::from_error(err.to_string());
+                        RpcResponse::new_error(Some(id), Some(res))
+                    }
+                });
+            }
+
+            // the response of a deferred command is sent by the event loop
+            match call_deferred(event_loop_proxy.clone(), &req.method, &params, reply) {
+                Ok(true) => return None,
+                Ok(false) => {}
+                Err(err) => {
+                    let res = Response::from_error(err.to_string());
+                    return Some(RpcResponse::new_error(Some(id), Some(res)));
+                }
+            }
+
             match call_func(event_loop_proxy.clone(), req.method.clone(), params.clone()) {
                 Ok(val) => Some(RpcResponse::new_result(Some(id), Some(val))),
 
@@ -350,10 +1546,10 @@ pub fn build_webview(app_config: App) -> Result<Application> {
                         if let Some(value) =
                             app_config.run_invoke_handler(event_loop_proxy.clone(), request)
                         {
-                            println!("Invoke handler found");
+                            log::debug!("invoke handler answered {}", req.method);
                             Some(RpcResponse::new_result(Some(id), Some(value)))
                         } else {
-                            println!("no invoke handler");
+                            log::warn!("no handler for rpc method {}", req.method);
                             None
                         }
                     }
@@ -366,8 +1562,20 @@ pub fn build_webview(app_config: App) -> Result<Application> {
         } else {
             match events::parse_event(&req.method) {
                 Ok(event) => {
-                    if let Err(err) = sender.send(event.clone()) {
-                        println!("{:?}", err.to_string());
+                    if let events::Event::VeloxEvent(events::VeloxEvents::Initialised) = event {
+                        let event = events::WindowEvents::PageLoaded(window_identifier.clone());
+                        if let Err(err) =
+                            event_loop_proxy.send_event(events::Event::WindowEvent(event))
+                        {
+                            log::error!("{}", err);
+                        }
+                    }
+
+                    for plugin in &app_config.plugins {
+                        plugin
+                            .lock()
+                            .unwrap()
+                            .event_received(Some(&window_identifier), &event);
                     }
 
                     let request = Request::Event(event);
@@ -376,65 +1584,98 @@ pub fn build_webview(app_config: App) -> Result<Application> {
                 }
 
                 Err(err) => {
-                    println!("{:?}", err.to_string());
+                    log::error!("{}", err);
                 }
             };
             None
         }
-    };
-
-    let window = WindowBuilder::new()
-        .with_title(&app_conf.name)
-        .with_visible(false)
-        .build(&event_loop)
-        .unwrap();
-
-    let id = window.id();
-
-    let webview = WebViewBuilder::new(window)?
-        .with_url(&app_conf.url)?
-        .with_rpc_handler(handler)
-        .with_initialization_script(&init_script())
-        .build()?;
-
-    if let Some(_content) = app_conf.clone().splashscreen {
-        plugin::splashscreen::show_splashscreen(event_loop.create_proxy(), app_conf, receiver)
-            .unwrap();
-    } else {
-        webview.window().set_visible(true);
     }
-
-    let mut app = Application::new(Some(event_loop));
-
-    let window_identifier = WebviewWindow {
-        identifier: "main_window".to_string(),
-        window_id: id,
-        webview,
-    };
-
-    app.add_window(window_identifier);
-
-    Ok(app)
 }
 
 // initialise scripts that will be injected to javascript
-fn init_script() -> String {
+fn init_script(window_identifier: &str, app: &App) -> String {
     let velox_script = include_str!("js/velox.js");
+    let log_script = include_str!("js/log.js");
+    let events_script = include_str!("js/events.js");
+    let window_script = include_str!("js/window.js");
+    let menu_script = include_str!("js/menu.js");
+    let tray_script = include_str!("js/tray.js");
+    let plugin_script = include_str!("js/plugin.js");
+    let frameless_script = include_str!("js/frameless.js");
     let test_script = include_str!("js/velox.test.js");
+    let zoom_script = if app.zoom_hotkeys {
+        include_str!("js/zoom.js")
+    } else {
+        ""
+    };
+    let plugin_scripts: String = app
+        .plugins
+        .iter()
+        .filter_map(|plugin| plugin.lock().unwrap().initialization_script())
+        .collect::<Vec<String>>()
+        .join("\n");
+    let debug_script = if app.debug {
+        include_str!("js/debug.js")
+    } else {
+        ""
+    };
 
     format!(
         r#"
                       {velox_script}
+                      {log_script}
+                      __VELOX__.windowIdentifier = {window_identifier};
+                      __VELOX__.app = {{
+                        // Returns the command-line arguments which were parsed with the `args` schema
+                        getMatches: function () {{
+                          return Promise.resolve({matches});
+                        }},
+                        // Returns the crash report of the previous run, or null if it didn't crash
+                        getPreviousCrash: function () {{
+                          return Promise.resolve({previous_crash});
+                        }},
+                        // Exits the app, for example from the tray while all windows are hidden
+                        exit: function () {{
+                          return __VELOX__.rpc.call("exit");
+                        }},
+                      }};
+                      {events_script}
+                      {window_script}
+                      {menu_script}
+                      {tray_script}
+                      {plugin_script}
+                      {frameless_script}
                     {test_script}
                       if (window.rpc) {{
                         window.rpc.notify(JSON.stringify({{veloxEvent: "initialised"}}))
                         window.addEventListener('load', function () {{
                           window.rpc.notify(JSON.stringify({{veloxEvent: "loaded"}}))
                         }})
+                        window.addEventListener('error', function (event) {{
+                          if (event.target instanceof HTMLScriptElement || event.target instanceof HTMLLinkElement) {{
+                            window.rpc.notify(JSON.stringify({{resourceEvent: "error"}}))
+                          }}
+                        }}, true)
                             __VELOX__.rpc = window.rpc;
                       }}
+                      {plugin_scripts}
+                      {zoom_script}
+                      {debug_script}
                     "#,
         velox_script = velox_script,
+        log_script = log_script,
+        window_identifier = wry::Value::from(window_identifier),
+        matches = json!(app.matches.args),
+        previous_crash = json!(app.previous_crash),
+        events_script = events_script,
+        window_script = window_script,
+        menu_script = menu_script,
+        tray_script = tray_script,
+        plugin_script = plugin_script,
+        plugin_scripts = plugin_scripts,
+        frameless_script = frameless_script,
         test_script = test_script,
+        zoom_script = zoom_script,
+        debug_script = debug_script,
     )
 }
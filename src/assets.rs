@@ -1,7 +1,80 @@
 use crate::config;
-use crate::Result;
+use crate::{Error, Result};
 use std::env;
-use std::path::Path;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use wry::application::window::Icon;
+
+/// Resolves a path which is relative to the asset directory.
+/// Fails for paths which lead out of the asset directory, like absolute paths,
+/// `..` or symlinks to other directories.
+pub fn resolve(asset_dir: &str, path: &str) -> Result<PathBuf> {
+    let root = fs::canonicalize(asset_dir)?;
+    let file = fs::canonicalize(root.join(path))?;
+
+    if file.starts_with(&root) {
+        Ok(file)
+    } else {
+        Err(Error::CommandError {
+            detail: format!("`{}` is not inside of the asset directory", path),
+        })
+    }
+}
+
+/// Loads a PNG or ICO file in the asset directory as window icon.
+pub fn load_icon(asset_dir: &str, path: &str) -> Result<Icon> {
+    let bytes = fs::read(resolve(asset_dir, path)?)?;
+    let image = image::load_from_memory(&bytes)?.into_rgba8();
+    let (width, height) = image.dimensions();
+
+    Ok(Icon::from_rgba(image.into_raw(), width, height)?)
+}
+
+/// Turns the name of an app into a name for files and directories.
+pub fn file_name(app_name: &str) -> String {
+    app_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Returns the directory where an app keeps its data, like
+/// `$XDG_DATA_HOME/<app name>` on linux and `%APPDATA%\<app name>` on windows.
+/// Fails if the variables which point to it are not set to an absolute path.
+pub fn data_dir(app_name: &str) -> Result<PathBuf> {
+    let name = file_name(app_name);
+    let var = |name: &str| {
+        env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+    };
+    let home = || {
+        var("HOME").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "HOME is not set to an absolute path",
+            )
+        })
+    };
+
+    let base = if cfg!(target_os = "windows") {
+        var("APPDATA").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "APPDATA is not set to an absolute path",
+            )
+        })?
+    } else if cfg!(target_os = "macos") {
+        home()?.join("Library/Application Support")
+    } else {
+        match var("XDG_DATA_HOME") {
+            Some(dir) => dir,
+            None => home()?.join(".local/share"),
+        }
+    };
+    Ok(base.join(name))
+}
 
 // Returns the path of where the assets are located
 pub fn get_asset_path(config: &config::VeloxConfig) -> Result<String> {
@@ -51,3 +124,30 @@ pub fn get_asset_path(config: &config::VeloxConfig) -> Result<String> {
         unimplemented!();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_stays_in_asset_dir() {
+        let root = env::temp_dir().join(format!("velox-assets-{}", std::process::id()));
+        fs::create_dir_all(root.join("icons")).unwrap();
+        fs::write(root.join("icons/app.png"), b"").unwrap();
+        let asset_dir = root.join("icons");
+        let asset_dir = asset_dir.to_str().unwrap();
+
+        assert_eq!(
+            resolve(asset_dir, "app.png").unwrap(),
+            fs::canonicalize(root.join("icons/app.png")).unwrap()
+        );
+        assert!(resolve(asset_dir, "../icons/app.png").is_ok());
+
+        fs::write(root.join("secret"), b"").unwrap();
+        assert!(resolve(asset_dir, "../secret").is_err());
+        assert!(resolve(asset_dir, root.join("secret").to_str().unwrap()).is_err());
+        assert!(resolve(asset_dir, "missing.png").is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! Parses the command-line arguments of an app with the `args` schema of the velox config file.
+//! `--help` and `--version` are generated from the schema, the name and the description of the app.
+//! Apps without a schema don't parse their arguments at all.
+
+use crate::config::{ArgConfig, ArgsConfig};
+
+use std::collections::HashMap;
+
+use clap::{App as ClapApp, Arg, ErrorKind};
+use serde::{Deserialize, Serialize};
+
+/// Values of the command-line arguments of an app by name.
+/// Flags are booleans, options and positionals are strings, arrays of strings
+/// if they can be given multiple times, or null if they are missing.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ArgMatches {
+    pub args: HashMap<String, wry::Value>,
+}
+
+impl ArgMatches {
+    /// Returns whether a flag was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.args.get(name).and_then(|value| value.as_bool()) == Some(true)
+    }
+
+    /// Returns the value of an option or positional.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.args.get(name).and_then(|value| value.as_str())
+    }
+
+    /// Returns all values of an option or positional which can be given multiple times.
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        match self.args.get(name) {
+            Some(wry::Value::Array(values)) => values.iter().filter_map(|v| v.as_str()).collect(),
+            Some(wry::Value::String(value)) => vec![value],
+            _ => vec![],
+        }
+    }
+}
+
+/// Parses the arguments of this process.
+/// Prints help or version and exits if the arguments ask for it, invalid arguments
+/// are logged and leave all matches empty.
+pub fn parse_args(
+    name: &str,
+    description: &str,
+    version: Option<&str>,
+    schema: &ArgsConfig,
+) -> ArgMatches {
+    match parse_args_from(name, description, version, schema, std::env::args()) {
+        Ok(matches) => matches,
+        Err(err) => match err.kind {
+            ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => err.exit(),
+            _ => {
+                log::error!("{}", err.message);
+                ArgMatches::default()
+            }
+        },
+    }
+}
+
+/// Parses a list of arguments, the first one is the path of the executable.
+/// Returns empty matches without looking at the arguments if the schema is empty.
+pub fn parse_args_from<I: IntoIterator<Item = String>>(
+    name: &str,
+    description: &str,
+    version: Option<&str>,
+    schema: &ArgsConfig,
+    args: I,
+) -> Result<ArgMatches, clap::Error> {
+    if schema.flags.is_empty() && schema.options.is_empty() && schema.positionals.is_empty() {
+        return Ok(ArgMatches::default());
+    }
+
+    let mut app = ClapApp::new(name).about(description);
+    if let Some(version) = version {
+        app = app.version(version);
+    }
+
+    for flag in &schema.flags {
+        app = app.arg(arg(flag).long(&flag.name).multiple(false));
+    }
+    for option in &schema.options {
+        app = app.arg(arg(option).long(&option.name).takes_value(true));
+    }
+    for (index, positional) in schema.positionals.iter().enumerate() {
+        app = app.arg(arg(positional).index(index as u64 + 1));
+    }
+
+    let matches = app.get_matches_from_safe(args)?;
+
+    let mut args = HashMap::new();
+    for flag in &schema.flags {
+        args.insert(
+            flag.name.clone(),
+            wry::Value::from(matches.is_present(&flag.name)),
+        );
+    }
+    for arg in schema.options.iter().chain(&schema.positionals) {
+        let value = match matches.values_of(&arg.name) {
+            Some(values) if arg.multiple => wry::Value::from(values.collect::<Vec<&str>>()),
+            Some(mut values) => wry::Value::from(values.next()),
+            None => wry::Value::Null,
+        };
+        args.insert(arg.name.clone(), value);
+    }
+
+    Ok(ArgMatches { args })
+}
+
+/// Converts an argument of the schema to a clap argument, positionals have no long name.
+fn arg(config: &ArgConfig) -> Arg<'_, '_> {
+    let mut arg = Arg::with_name(&config.name)
+        .required(config.required)
+        .multiple(config.multiple);
+
+    if let Some(description) = &config.description {
+        arg = arg.help(description);
+    }
+    if let Some(short) = &config.short {
+        arg = arg.short(short);
+    }
+    if let Some(default) = &config.default {
+        arg = arg.default_value(default);
+    }
+    if !config.possible_values.is_empty() {
+        let values: Vec<&str> = config.possible_values.iter().map(String::as_str).collect();
+        arg = arg.possible_values(&values);
+    }
+    arg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(schema: wry::Value, args: &[&str]) -> Result<ArgMatches, clap::Error> {
+        let schema: ArgsConfig = serde_json::from_value(schema).unwrap();
+        let args = std::iter::once("app")
+            .chain(args.iter().copied())
+            .map(String::from);
+        parse_args_from("app", "An app", Some("1.0.0"), &schema, args)
+    }
+
+    #[test]
+    fn empty_schema_ignores_arguments() {
+        let matches = parse(json!({}), &["--unknown", "file.txt"]).unwrap();
+        assert!(matches.args.is_empty());
+    }
+
+    #[test]
+    fn parses_flags_options_and_positionals() {
+        let schema = json!({
+            "flags": [{ "name": "verbose", "short": "v" }, { "name": "quiet" }],
+            "options": [{ "name": "theme", "possible_values": ["light", "dark"] }],
+            "positionals": [{ "name": "file", "multiple": true }]
+        });
+        let matches = parse(schema, &["-v", "--theme", "dark", "a.txt", "b.txt"]).unwrap();
+
+        assert!(matches.flag("verbose"));
+        assert!(!matches.flag("quiet"));
+        assert_eq!(matches.value("theme"), Some("dark"));
+        assert_eq!(matches.values("file"), vec!["a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn missing_values_are_null_or_default() {
+        let schema = json!({
+            "options": [{ "name": "theme", "default": "light" }, { "name": "lang" }]
+        });
+        let matches = parse(schema, &[]).unwrap();
+
+        assert_eq!(matches.value("theme"), Some("light"));
+        assert_eq!(matches.args["lang"], wry::Value::Null);
+        assert!(matches.values("lang").is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        let schema = json!({
+            "options": [{ "name": "theme", "possible_values": ["light", "dark"] }]
+        });
+
+        let err = parse(schema.clone(), &["--theme", "blue"]).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::InvalidValue);
+        let err = parse(schema, &["--unknown"]).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::UnknownArgument);
+    }
+
+    #[test]
+    fn help_and_version_are_errors_for_the_caller() {
+        let schema = json!({ "flags": [{ "name": "verbose" }] });
+
+        let err = parse(schema.clone(), &["--help"]).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::HelpDisplayed);
+        assert!(err.message.contains("An app"));
+        let err = parse(schema, &["--version"]).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::VersionDisplayed);
+    }
+}
@@ -1,5 +1,8 @@
+use crate::app::ContentType;
+use crate::menu::MenuItem;
 use crate::Result;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use wry::application::{dpi::LogicalSize, window::WindowBuilder};
 
 /// The `metadata` section of the package configuration.
 ///
@@ -57,6 +60,307 @@ pub struct CargoSettings {
     bin: Option<Vec<BinarySettings>>,
 }
 
+/// A window that is declared in the `windows` section of the config file.
+///
+/// # Example velox.conf.json
+/// ```json
+/// "windows": [
+///     {
+///         "identifier": "inspector",
+///         "title": "Inspector",
+///         "content": { "asset": "inspector.html" },
+///         "width": 400,
+///         "height": 600,
+///         "startup": false
+///     }
+/// ]
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WindowConfig {
+    /// identifier of the window, "main_window" configures the main window of the app.
+    pub identifier: String,
+    /// title of the window, defaults to the name of the app.
+    pub title: Option<String>,
+    /// content of the window, a url, `{"html": ...}` or `{"asset": ...}`.
+    /// Ignored for the main window.
+    #[serde(default, deserialize_with = "deserialize_optional_content")]
+    pub content: Option<ContentType>,
+    /// width of the window in logical pixels.
+    pub width: Option<f64>,
+    /// height of the window in logical pixels.
+    pub height: Option<f64>,
+    /// whether the window has a titlebar and borders.
+    #[serde(default = "default_true")]
+    pub decorations: bool,
+    /// whether the window is visible when it is created.
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    /// icon of the window, a PNG or ICO file in the asset directory.
+    /// Defaults to the icon of the app.
+    pub icon: Option<String>,
+    /// menu bar of the window, the main window defaults to the menu of the app
+    pub menu: Option<Vec<MenuItem>>,
+    /// whether the window is created when the app starts.
+    /// Other windows can be opened later on with their identifier.
+    #[serde(default = "default_true")]
+    pub startup: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Accepts a plain string as url, besides `{"url": ...}`, `{"html": ...}` and `{"asset": ...}`.
+fn deserialize_content<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<ContentType, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Content {
+        Url(String),
+        Typed(ContentType),
+    }
+
+    Ok(match Content::deserialize(deserializer)? {
+        Content::Url(url) => ContentType::Url(url),
+        Content::Typed(content) => content,
+    })
+}
+
+fn deserialize_optional_content<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<ContentType>, D::Error> {
+    #[derive(Deserialize)]
+    struct Content(#[serde(deserialize_with = "deserialize_content")] ContentType);
+
+    // `null` is no content, like a missing field
+    Ok(Option::<Content>::deserialize(deserializer)?.map(|Content(content)| content))
+}
+
+fn default_splashscreen_timeout() -> u64 {
+    30_000
+}
+
+/// An argument in the `args` section of the velox config file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArgConfig {
+    /// name of the argument, flags and options are given as `--name`.
+    pub name: String,
+    /// single letter which can be given as `-s` instead of the name.
+    pub short: Option<String>,
+    /// description of the argument which is shown by `--help`.
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// whether the argument can be given multiple times.
+    #[serde(default)]
+    pub multiple: bool,
+    /// value of an option or positional which is not given.
+    pub default: Option<String>,
+    /// values which are allowed for an option or positional.
+    #[serde(default)]
+    pub possible_values: Vec<String>,
+}
+
+/// The `log` section of the velox config file.
+///
+/// # Example
+/// ```json
+/// "log": {
+///     "level": "warn",
+///     "max_size": 1048576,
+///     "max_files": 3
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LogConfig {
+    /// One of "off", "error", "warn", "info", "debug" or "trace"
+    pub level: String,
+    /// Whether records are written to the log file in the data directory
+    pub file: bool,
+    /// Size in bytes after which the log file is rotated
+    pub max_size: u64,
+    /// Number of log files which are kept, including the current one
+    pub max_files: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            file: true,
+            max_size: 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+/// The `updater` section of the velox config file.
+///
+/// # Example
+/// ```json
+/// "updater": {
+///     "endpoint": "https://example.com/releases/latest.json",
+///     "pubkey": "<base64 ed25519 public key>"
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdaterConfig {
+    #[serde(default = "default_true")]
+    pub active: bool,
+    /// Url of the manifest which describes the newest release
+    pub endpoint: String,
+    /// Base64 encoded ed25519 public key which signed the releases
+    pub pubkey: String,
+    /// Whether the app checks for updates when it starts
+    #[serde(default)]
+    pub check_on_startup: bool,
+}
+
+/// The `tray` section of the velox config file, see `tray`.
+///
+/// # Example
+/// ```json
+/// "tray": {
+///     "tooltip": "Minimal",
+///     "hide_on_close": true,
+///     "menu": [{ "id": "quit", "title": "Quit" }]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TrayConfig {
+    /// Icon of the tray, a PNG or ICO file in the asset directory.
+    /// Defaults to the icon of the app.
+    pub icon: Option<String>,
+    /// Text which is shown when the mouse hovers over the tray
+    pub tooltip: Option<String>,
+    pub menu: Vec<MenuItem>,
+    /// Whether closing the main window hides it to the tray instead of closing it.
+    /// A click on the tray shows it again. Only supported on linux, the trays of
+    /// Windows and macOS don't emit clicks.
+    pub hide_on_close: bool,
+}
+
+/// The `args` section of the velox config file, describes the command-line arguments of the app.
+///
+/// # Example
+/// ```json
+/// "args": {
+///     "flags": [{ "name": "verbose", "short": "v", "description": "Print more output" }],
+///     "options": [{ "name": "theme", "possible_values": ["light", "dark"] }],
+///     "positionals": [{ "name": "file", "multiple": true }]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ArgsConfig {
+    /// arguments without a value, like `--verbose`.
+    pub flags: Vec<ArgConfig>,
+    /// arguments with a value, like `--theme dark`.
+    pub options: Vec<ArgConfig>,
+    /// arguments which are identified by their position.
+    pub positionals: Vec<ArgConfig>,
+    /// version which is shown by `--version`, defaults to the version in Cargo.toml.
+    pub version: Option<String>,
+}
+
+/// The `splashscreen` section of the velox config file.
+/// The splashscreen is shown until the main window finishes loading.
+///
+/// # Example
+/// ```json
+/// "splashscreen": {
+///     "enable": true,
+///     "content": { "asset": "splash.html" },
+///     "width": 400,
+///     "height": 300,
+///     "timeout": 10000,
+///     "min_duration": 500,
+///     "error_content": { "asset": "error.html" }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SplashscreenConfig {
+    /// whether the splashscreen is shown.
+    #[serde(default = "default_true")]
+    pub enable: bool,
+    /// content of the splashscreen, a url, `{"html": ...}` or `{"asset": ...}`.
+    #[serde(deserialize_with = "deserialize_content")]
+    pub content: ContentType,
+    /// width of the splashscreen in logical pixels.
+    pub width: Option<f64>,
+    /// height of the splashscreen in logical pixels.
+    pub height: Option<f64>,
+    /// whether the splashscreen has a titlebar and borders.
+    #[serde(default)]
+    pub decorations: bool,
+    /// milliseconds to wait for the main window before it is shown anyway.
+    /// With `manual_close` the splashscreen stays open and a warning is logged instead.
+    #[serde(default = "default_splashscreen_timeout")]
+    pub timeout: u64,
+    /// minimum milliseconds the splashscreen is shown, avoids flicker on fast loads.
+    #[serde(default)]
+    pub min_duration: u64,
+    /// whether the app closes the splashscreen itself with `__VELOX__.splash.close()`
+    /// instead of when the main window finished loading.
+    #[serde(default)]
+    pub manual_close: bool,
+    /// content which the main window shows instead if loading failed or timed out.
+    #[serde(default, deserialize_with = "deserialize_optional_content")]
+    pub error_content: Option<ContentType>,
+}
+
+impl SplashscreenConfig {
+    /// Returns a splashscreen without decorations which shows `content`.
+    pub fn new(content: ContentType) -> Self {
+        Self {
+            enable: true,
+            content,
+            width: None,
+            height: None,
+            decorations: false,
+            timeout: default_splashscreen_timeout(),
+            min_duration: 0,
+            manual_close: false,
+            error_content: None,
+        }
+    }
+}
+
+impl WindowConfig {
+    /// Returns a window config with default attributes.
+    pub fn new(identifier: &str) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            title: None,
+            content: None,
+            width: None,
+            height: None,
+            decorations: true,
+            visible: true,
+            icon: None,
+            menu: None,
+            startup: true,
+        }
+    }
+
+    /// Returns a window builder with all the attributes of this window.
+    pub fn window_builder(&self, app_name: &str) -> WindowBuilder {
+        let mut window_builder = WindowBuilder::new()
+            .with_title(self.title.as_deref().unwrap_or(app_name))
+            .with_decorations(self.decorations)
+            .with_visible(self.visible);
+
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            window_builder = window_builder.with_inner_size(LogicalSize::new(width, height));
+        }
+
+        window_builder
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct VeloxConfig {
     ///title of the app
@@ -67,6 +371,34 @@ pub struct VeloxConfig {
     pub dev_server_url: String,
     pub package_manager: String,
     pub build_dir: String,
+    /// splashscreen which is shown while the app is loading
+    pub splashscreen: Option<SplashscreenConfig>,
+    /// command-line arguments of the app
+    #[serde(default)]
+    pub args: ArgsConfig,
+    /// whether later launches of the app are forwarded to the running instance
+    #[serde(default)]
+    pub single_instance: bool,
+    /// directory with plugins which are loaded from shared libraries,
+    /// relative to the directory of the executable
+    pub plugin_dir: Option<String>,
+    /// icon of all windows, a PNG or ICO file in the asset directory
+    pub icon: Option<String>,
+    /// menu bar of the main window, see `menu`
+    pub menu: Option<Vec<MenuItem>>,
+    /// icon of the app in the system tray
+    pub tray: Option<TrayConfig>,
+    /// where the app looks for updates
+    pub updater: Option<UpdaterConfig>,
+    /// level and log file of the app
+    #[serde(default)]
+    pub log: LogConfig,
+    /// windows of the app
+    #[serde(default)]
+    pub windows: Vec<WindowConfig>,
+    /// Whether Ctrl + Plus, Ctrl + Minus and Ctrl + 0 change the zoom of a window
+    #[serde(default)]
+    pub zoom_hotkeys: bool,
 }
 
 pub fn parse_cargo_config() -> Result<CargoSettings> {
@@ -0,0 +1,179 @@
+//! Writes a crash report when an app panics.
+//!
+//! The panic hook saves every report to its own file in `<data dir>/crash-reports/`,
+//! see `assets::data_dir`. A native error dialog is only shown when the panic ends the app,
+//! which is a panic on the main thread or any panic when panics abort.
+//! On the next launch `previous_crash` returns the latest report once and moves all reports
+//! to `<data dir>/crashes/`, so apps can offer to send them.
+
+use crate::{assets, logger, Result};
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io::Write;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of reports which this process saved, keeps their file names unique
+static SAVED_REPORTS: AtomicUsize = AtomicUsize::new(0);
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CrashReport {
+    /// Name of the app from the config file
+    pub app_name: String,
+    pub velox_version: String,
+    /// Local time of the crash
+    pub time: String,
+    pub message: String,
+    /// File, line and column of the panic
+    pub location: Option<String>,
+    pub thread: String,
+    pub backtrace: String,
+    /// Last lines which were logged before the crash
+    pub log: Vec<String>,
+}
+
+/// Installs the panic hook which writes crash reports for an app.
+/// Must be called on the main thread. The previous hook still runs after the report was written.
+pub fn install(app_name: &str) {
+    let app_name = app_name.to_string();
+    let main_thread = std::thread::current().id();
+    let previous_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+        let location = info.location().map(|location| location.to_string());
+
+        // the logger could be the one that panicked, the previous hook prints the panic
+        let report = CrashReport::new(&app_name, message, location);
+        let saved = report.save();
+        if let Err(err) = &saved {
+            eprintln!("could not save crash report: {}", err);
+        }
+
+        // other threads can panic without ending the app, or be caught with `catch_unwind`
+        if cfg!(panic = "abort") || std::thread::current().id() == main_thread {
+            show_dialog(&report, saved.ok());
+        }
+
+        previous_hook(info);
+    }));
+}
+
+/// Returns the latest crash report of the previous runs if the app crashed, only once.
+/// All unread reports are moved to `<data dir>/crashes/`.
+pub fn previous_crash(app_name: &str) -> Option<CrashReport> {
+    let dir = reports_dir(app_name).ok()?;
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    // the names start with the time of the crash
+    paths.sort();
+
+    let archive = dir.with_file_name("crashes");
+    let mut latest = None;
+    for path in paths {
+        if let Some(report) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<CrashReport>(&json).ok())
+        {
+            latest = Some(report);
+        }
+
+        // keep the report, but don't return it again
+        let moved = fs::create_dir_all(&archive)
+            .and_then(|_| fs::rename(&path, archive.join(path.file_name().unwrap())));
+        if let Err(err) = moved {
+            log::error!("could not archive crash report: {}", err);
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    latest
+}
+
+impl CrashReport {
+    fn new(app_name: &str, message: String, location: Option<String>) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            velox_version: env!("CARGO_PKG_VERSION").to_string(),
+            time: chrono::Local::now().to_rfc3339(),
+            message,
+            location,
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("unnamed")
+                .to_string(),
+            backtrace: Backtrace::force_capture().to_string(),
+            log: logger::recent_lines(),
+        }
+    }
+
+    /// Writes the report for the next launch and returns its path.
+    /// Reports are never overwritten, also not the ones of other threads or processes.
+    fn save(&self) -> Result<PathBuf> {
+        let dir = reports_dir(&self.app_name)?;
+        fs::create_dir_all(&dir)?;
+
+        // YYYYMMDDhhmmss of the time of the crash
+        let time: String = self
+            .time
+            .chars()
+            .filter(char::is_ascii_digit)
+            .take(14)
+            .collect();
+        let path = dir.join(format!(
+            "crash-{}-{}-{}.json",
+            time,
+            std::process::id(),
+            SAVED_REPORTS.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?
+            .write_all(&serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// Directory of the reports which were not returned by `previous_crash` yet
+fn reports_dir(app_name: &str) -> Result<PathBuf> {
+    Ok(assets::data_dir(app_name)?.join("crash-reports"))
+}
+
+fn show_dialog(report: &CrashReport, path: Option<PathBuf>) {
+    use tinyfiledialogs::{message_box_ok, MessageBoxIcon};
+
+    // without a display the dialog would wait for input on the terminal
+    if cfg!(target_os = "linux")
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+    {
+        return;
+    }
+
+    // the dialogs of some platforms can't show quotes
+    let mut message =
+        format!("{} crashed: {}", report.app_name, report.message).replace(['"', '\''], "");
+    if let Some(path) = path {
+        message += &format!("\n\nA crash report was saved to {}", path.display());
+    }
+
+    message_box_ok(&report.app_name, &message, MessageBoxIcon::Error);
+}
@@ -1,11 +1,42 @@
 //! This module includes several events that velox emits when an app is being run.
 
+use crate::app::ContentType;
+use crate::menu::{MenuItem, MenuItemUpdate};
+use crate::store::StoreAction;
+use crate::tray::{TrayAction, TrayEvent};
+use crate::window::{ResizeDirection, WindowOptions};
 use crate::Result;
 
+use crossbeam_channel::{unbounded, Receiver, Sender};
 use serde::{Deserialize, Serialize};
 
 type Identifier = String;
 
+/// Describes where the result of a request is delivered to, when it can only be
+/// answered by the event loop.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Reply {
+    /// Resolves a pending javascript call of a webview window
+    Rpc {
+        /// Identifier of the window which made the call
+        window: Identifier,
+        /// Id of the JSON-RPC request
+        id: wry::Value,
+    },
+    /// Sends the result through a channel, used when calling velox-api from rust
+    #[serde(skip)]
+    Channel(Sender<Result<wry::Value>>),
+}
+
+impl Reply {
+    /// Creates a reply which can be received from rust.
+    /// Note- Don't block on the receiver in the thread which runs the event loop.
+    pub fn channel() -> (Self, Receiver<Result<wry::Value>>) {
+        let (sender, receiver) = unbounded();
+        (Reply::Channel(sender), receiver)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum ResourceEvent {
@@ -28,6 +59,45 @@ pub enum NetworkEvent {
 pub enum VeloxEvents {
     Initialised,
     Loaded,
+    /// Loading progress of the app which is shown by the splashscreen
+    SplashscreenProgress {
+        percent: f64,
+        text: Option<String>,
+    },
+    /// The app is ready, closes the splashscreen and shows the main window
+    SplashscreenClose,
+    /// The app was launched again while single-instance mode is enabled
+    SecondInstance {
+        /// Command-line arguments of the later launch
+        args: Vec<String>,
+        /// Working directory of the later launch
+        cwd: String,
+    },
+    /// Looks for a newer release of the app
+    CheckUpdate,
+    /// Downloads and installs the newest release, then restarts the app
+    InstallUpdate,
+    /// Exits the app after the plugins ran their exit hooks
+    Exit,
+    /// An item of the menu bar of a window was activated
+    MenuActivated {
+        /// Identifier of the window
+        window: String,
+        /// Id of the menu item
+        id: String,
+        /// New state of a check item
+        checked: Option<bool>,
+    },
+    /// Changes the tray of the app
+    Tray(TrayAction),
+    /// The tray was clicked or an item of its menu was activated
+    TrayEvent(TrayEvent),
+    /// Reads or changes a persistent key-value store
+    Store {
+        store: String,
+        action: StoreAction,
+        reply: Option<Reply>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -35,8 +105,18 @@ pub enum WindowEvents {
     AddWindow {
         /// Identifier for a window. For example: "main_window"
         window_title: String,
-        content: String,
+        content: ContentType,
         identifier: Identifier,
+        /// Boxed, the options are larger than all other events
+        options: Box<WindowOptions>,
+        /// Receives `true` when the window was created, or the result of a modal window
+        /// when it closes
+        reply: Option<Reply>,
+    },
+    /// Opens a window which is declared in the config file
+    OpenWindow {
+        identifier: Identifier,
+        reply: Option<Reply>,
     },
     CloseWindow(Identifier),
     ShowWindow(Identifier),
@@ -56,6 +136,103 @@ pub enum WindowEvents {
         flag: bool,
         identifier: Identifier,
     },
+    /// Whether javascript decides itself if a window should close when the user requests it
+    InterceptClose {
+        flag: bool,
+        identifier: Identifier,
+    },
+    /// Moves a window with the mouse, used by drag regions of frameless windows
+    StartDrag(Identifier),
+    /// Resizes a window with the mouse, used by resize handles of frameless windows
+    StartResize {
+        direction: ResizeDirection,
+        identifier: Identifier,
+    },
+    /// Reloads the page of a window
+    Reload(Identifier),
+    /// A page was loaded in a window and ran the init script
+    PageLoaded(Identifier),
+    /// The page of a window could not be loaded, failed scripts or stylesheets don't count
+    LoadFailed(Identifier),
+    /// Loads a url in a window
+    Navigate {
+        url: String,
+        identifier: Identifier,
+    },
+    /// Goes back in the history of a window
+    GoBack(Identifier),
+    /// Goes forward in the history of a window
+    GoForward(Identifier),
+    /// Replaces the menu bar of a window
+    SetMenu {
+        menu: Vec<MenuItem>,
+        identifier: Identifier,
+    },
+    /// Changes the title, state or check mark of a menu item
+    UpdateMenuItem {
+        id: String,
+        update: MenuItemUpdate,
+        identifier: Identifier,
+    },
+    /// Sets the zoom factor of a window, `1.0` is the default
+    SetZoom {
+        factor: f64,
+        identifier: Identifier,
+    },
+    /// Evaluates a script in a window, `reply` receives the JSON-serialized result of the script
+    Eval {
+        script: String,
+        identifier: Identifier,
+        reply: Option<Reply>,
+    },
+    /// Result of a script which was evaluated with a reply, sent by javascript.
+    /// `token` is only known to the script, so pages can't answer for other scripts.
+    EvalResult {
+        identifier: Identifier,
+        token: String,
+        success: bool,
+        value: wry::Value,
+    },
+    /// Sets the icon of a window, `path` is a PNG or ICO file in the asset directory
+    SetIcon {
+        path: String,
+        identifier: Identifier,
+    },
+    /// Opens the web inspector of a window, only works in debug mode
+    OpenDevtools(Identifier),
+    /// Sets the result which a modal window returns to its opener
+    SetResult {
+        result: wry::Value,
+        identifier: Identifier,
+    },
+    /// Javascript accepted a close request of a window
+    ConfirmClose(Identifier),
+    /// Returns identifiers of all open windows
+    GetWindows(Reply),
+    /// Returns size, position and state of a window
+    GetWindowInfo {
+        identifier: Identifier,
+        reply: Reply,
+    },
+    /// Returns all monitors which are connected to the system
+    GetMonitors(Reply),
+    /// Sends a message from one window to another, or to all other windows if `target` is `None`
+    PostMessage {
+        source: Identifier,
+        target: Option<Identifier>,
+        payload: wry::Value,
+    },
+    /// Emits an event to the javascript listeners of a window, or of all windows if `target` is `None`
+    Emit {
+        event: String,
+        payload: wry::Value,
+        target: Option<Identifier>,
+    },
+    /// Moves a window to a monitor, `monitor` is an index into the list of monitors
+    SetMonitor {
+        monitor: usize,
+        identifier: Identifier,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1,7 +1,10 @@
 use crate::api::fs::file_system;
 use crate::api::notification::show_notification;
-use crate::api::{subprocess, window};
-use crate::{convert_to_json, events::Event, Error, Result};
+use crate::api::{app, menu, subprocess, tray, window};
+use crate::app::ContentType;
+use crate::events::{Event, Reply};
+use crate::window::WindowOptions;
+use crate::{convert_to_json, Error, Result};
 
 use wry::application::event_loop::EventLoopProxy;
 
@@ -12,15 +15,6 @@ pub fn call_func(
     params: Vec<wry::Value>,
 ) -> Result<wry::Value> {
     match func_name.as_str() {
-        "add_window" => {
-            let res = window::add_window(
-                serde_json::from_str(&params[0].to_string())?,
-                serde_json::from_str(&params[1].to_string())?,
-                event_proxy,
-            )?;
-            Ok(convert_to_json(res))
-        }
-
         "close_window" => {
             let res =
                 window::close_window(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
@@ -59,6 +53,161 @@ pub fn call_func(
             Ok(convert_to_json("success"))
         }
 
+        "start_drag" => {
+            window::start_drag(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        "start_resize" => {
+            window::start_resize(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "reload" => {
+            window::reload(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        "navigate" => {
+            window::navigate(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "go_back" => {
+            window::go_back(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        "go_forward" => {
+            window::go_forward(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        "set_zoom" => {
+            window::set_zoom(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "eval" => {
+            window::eval(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                None,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        // the window identifier is inserted by the rpc handler
+        "eval_result" => {
+            window::eval_result(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                params[2].as_bool().unwrap_or(false),
+                params.get(3).cloned().unwrap_or(wry::Value::Null),
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "set_icon" => {
+            window::set_icon(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "set_menu" => {
+            menu::set_menu(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "update_menu_item" => {
+            menu::update_item(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                serde_json::from_str(&params[2].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "tray" => {
+            tray::apply(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        "exit" => {
+            app::exit(event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        "open_devtools" => {
+            window::open_devtools(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        "set_result" => {
+            window::set_result(
+                params[0].clone(),
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "intercept_close" => {
+            window::intercept_close(
+                params[0].as_bool().unwrap(),
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "confirm_close" => {
+            window::confirm_close(serde_json::from_str(&params[0].to_string())?, event_proxy)?;
+            Ok(convert_to_json("success"))
+        }
+
+        // the source identifier is inserted by the rpc handler of the sending window
+        "post_message" => {
+            window::post_message(
+                serde_json::from_str(&params[0].to_string())?,
+                serde_json::from_str(&params[1].to_string())?,
+                params[2].clone(),
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
+        "set_monitor" => {
+            window::set_monitor(
+                params[0].as_u64().unwrap() as usize,
+                serde_json::from_str(&params[1].to_string())?,
+                event_proxy,
+            )?;
+            Ok(convert_to_json("success"))
+        }
+
         "show_notification" => {
             let res = show_notification(
                 serde_json::from_str(&params[0].to_string())?,
@@ -147,3 +296,59 @@ pub fn call_func(
         }),
     }
 }
+
+/// A request handler for commands which can only be answered by the event loop.
+/// The result is delivered to `reply` later on.
+/// Returns false if `func_name` is not one of these commands.
+pub fn call_deferred(
+    event_proxy: EventLoopProxy<Event>,
+    func_name: &str,
+    params: &[wry::Value],
+    reply: Reply,
+) -> Result<bool> {
+    match func_name {
+        "add_window" => {
+            // a plain string is the url of the window
+            let content = match &params[1] {
+                wry::Value::String(url) => ContentType::Url(url.clone()),
+                content => serde_json::from_value(content.clone())?,
+            };
+            let options = match params.get(2) {
+                Some(options) if !options.is_null() => serde_json::from_value(options.clone())?,
+                _ => WindowOptions::default(),
+            };
+            window::add_window(
+                serde_json::from_str(&params[0].to_string())?,
+                content,
+                options,
+                Some(reply),
+                event_proxy,
+            )
+        }
+
+        "open_window" => window::open_window(
+            serde_json::from_str(&params[0].to_string())?,
+            Some(reply),
+            event_proxy,
+        ),
+
+        "get_windows" => window::get_windows(reply, event_proxy),
+
+        "get_window_info" => window::get_window_info(
+            serde_json::from_str(&params[0].to_string())?,
+            reply,
+            event_proxy,
+        ),
+
+        "get_monitors" => window::get_monitors(reply, event_proxy),
+
+        "eval_with_result" => window::eval(
+            serde_json::from_str(&params[0].to_string())?,
+            serde_json::from_str(&params[1].to_string())?,
+            Some(reply),
+            event_proxy,
+        ),
+
+        _ => Ok(false),
+    }
+}
@@ -0,0 +1,46 @@
+// Logs every message between javascript and velox, only injected in debug mode.
+// The messages only go to the devtools console, forwarding them to velox would
+// send another message for every message.
+
+(function () {
+    if (!window.__VELOX__.rpc) {
+        return;
+    }
+
+    const rpc = window.__VELOX__.rpc;
+    const call = rpc.call;
+    const notify = rpc.notify;
+    const dispatch = window.__VELOX__.event._dispatch;
+    const identifier = window.__VELOX__.windowIdentifier;
+    const debug = (window.__VELOX__._console || console).debug;
+
+    rpc.call = function (method) {
+        const params = Array.prototype.slice.call(arguments, 1);
+        debug("[velox:" + identifier + "] call", method, params);
+
+        return call.apply(rpc, arguments).then(
+            function (res) {
+                debug("[velox:" + identifier + "] result", method, res);
+                return res;
+            },
+            function (err) {
+                debug("[velox:" + identifier + "] error", method, err);
+                throw err;
+            }
+        );
+    };
+
+    rpc.notify = function (method) {
+        debug("[velox:" + identifier + "] notify", method);
+        return notify.apply(rpc, arguments);
+    };
+
+    window.__VELOX__.event._dispatch = function (name, payload) {
+        debug("[velox:" + identifier + "] event", name, payload);
+        return dispatch.call(window.__VELOX__.event, name, payload);
+    };
+
+    window.__VELOX__.window.openDevtools = function (windowIdentifier) {
+        return rpc.call("open_devtools", windowIdentifier || identifier);
+    };
+})();
@@ -0,0 +1,40 @@
+// Event bus for events that velox emits from rust to a webview window.
+// Rust calls `__VELOX__.event._dispatch(name, payload)` through the webview dispatcher.
+
+(function () {
+    const listeners = {};
+
+    window.__VELOX__.event = {
+        // Registers a callback for an event and returns a function which removes it again
+        listen: function (name, callback) {
+            (listeners[name] = listeners[name] || []).push(callback);
+            return function () {
+                listeners[name] = (listeners[name] || []).filter(function (cb) {
+                    return cb !== callback;
+                });
+            };
+        },
+
+        // Runs every listener of an event, returns true if one of them called `preventDefault`
+        _dispatch: async function (name, payload) {
+            let prevented = false;
+            const event = {
+                name: name,
+                payload: payload,
+                preventDefault: function () {
+                    prevented = true;
+                },
+            };
+
+            for (const callback of (listeners[name] || []).slice()) {
+                try {
+                    await callback(event);
+                } catch (err) {
+                    console.error(err);
+                }
+            }
+
+            return prevented;
+        },
+    };
+})();
@@ -0,0 +1,32 @@
+// Drag regions and resize handles for windows without decorations.
+//
+// <div data-velox-drag-region>...</div> moves the window,
+// <div data-velox-resize-handle="south-east"></div> resizes it.
+
+(function () {
+    const api = window.__VELOX__.window;
+    const interactive = "a, button, input, select, textarea, [contenteditable]";
+
+    document.addEventListener("mousedown", function (event) {
+        if (event.button !== 0 || !(event.target instanceof Element)) {
+            return;
+        }
+
+        const handle = event.target.closest("[data-velox-resize-handle]");
+        if (handle) {
+            event.preventDefault();
+            api.startResize(handle.getAttribute("data-velox-resize-handle"));
+            return;
+        }
+
+        // controls inside of a titlebar keep working
+        if (event.target.closest(interactive)) {
+            return;
+        }
+
+        if (event.target.closest("[data-velox-drag-region]")) {
+            event.preventDefault();
+            api.startDrag();
+        }
+    });
+})();
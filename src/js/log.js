@@ -0,0 +1,58 @@
+// Forwards console output and uncaught errors to the log of velox,
+// where they are written with the identifier of this window.
+// The unwrapped console methods stay available as `__VELOX__._console`.
+
+(function () {
+    // debug.js wraps `rpc.notify` with console output, keep the original to not recurse
+    const rpc = window.rpc;
+    const notify = rpc && rpc.notify;
+    const unwrapped = {};
+
+    function format(value) {
+        if (value instanceof Error) {
+            return value.stack || String(value);
+        }
+        if (typeof value === "object" && value !== null) {
+            try {
+                return JSON.stringify(value);
+            } catch (e) {
+                return String(value);
+            }
+        }
+        return String(value);
+    }
+
+    function forward(level, values) {
+        if (notify) {
+            notify.apply(rpc, ["log", level].concat(values.map(format)));
+        }
+    }
+
+    ["log", "info", "debug", "warn", "error"].forEach(function (method) {
+        const original = console[method];
+        const level = method === "log" ? "info" : method;
+        unwrapped[method] = original.bind(console);
+
+        console[method] = function () {
+            const values = Array.prototype.slice.call(arguments);
+            forward(level, values);
+            return original.apply(console, values);
+        };
+    });
+
+    window.__VELOX__._console = unwrapped;
+
+    window.addEventListener("error", function (event) {
+        // failed scripts and stylesheets are reported as resource events
+        if (event instanceof ErrorEvent) {
+            forward("error", [
+                "uncaught " + (event.error || event.message),
+                "at " + event.filename + ":" + event.lineno + ":" + event.colno,
+            ]);
+        }
+    });
+
+    window.addEventListener("unhandledrejection", function (event) {
+        forward("error", ["unhandled rejection", event.reason]);
+    });
+})();
@@ -0,0 +1,39 @@
+// Menu API of velox. Menus use the format of the `menu` section of velox.conf.json.
+//
+// Every function takes an optional window identifier and defaults to this window.
+
+(function () {
+    function target(windowIdentifier) {
+        return windowIdentifier || window.__VELOX__.windowIdentifier;
+    }
+
+    window.__VELOX__.menu = {
+        // Replaces the menu bar of a window, which may only contain submenus
+        setMenu: function (menu, windowIdentifier) {
+            return window.__VELOX__.rpc.call("set_menu", menu, target(windowIdentifier));
+        },
+
+        // Changes the `title`, `enabled` or `checked` state of the item with an id
+        update: function (id, update, windowIdentifier) {
+            return window.__VELOX__.rpc.call(
+                "update_menu_item", id, update, target(windowIdentifier)
+            );
+        },
+
+        setEnabled: function (id, flag, windowIdentifier) {
+            return this.update(id, { enabled: flag }, windowIdentifier);
+        },
+
+        setChecked: function (id, flag, windowIdentifier) {
+            return this.update(id, { checked: flag }, windowIdentifier);
+        },
+
+        // Listens for activated items of this window, the callback receives the id
+        // and the new state of check items, null for other items.
+        onActivate: function (callback) {
+            return window.__VELOX__.event.listen("menu", function (event) {
+                return callback(event.payload.id, event.payload.checked);
+            });
+        },
+    };
+})();
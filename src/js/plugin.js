@@ -0,0 +1,13 @@
+// Calls commands of plugins which are registered with `AppBuilder::plugin`.
+
+(function () {
+    window.__VELOX__.plugin = {
+        // Runs a command of a plugin and resolves with its result
+        invoke: function (pluginName, command) {
+            const params = Array.prototype.slice.call(arguments, 2);
+            return window.__VELOX__.rpc.call.apply(
+                window.__VELOX__.rpc, ["plugin:" + pluginName + "|" + command].concat(params)
+            );
+        },
+    };
+})();
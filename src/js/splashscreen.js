@@ -0,0 +1,39 @@
+// Splashscreen API, injected by the splashscreen plugin. The main window reports its
+// loading progress and closes the splashscreen, the splashscreen window shows the progress.
+//
+// Inside of the splashscreen <progress data-velox-splash-progress max="100"> and
+// elements with `data-velox-splash-text` are updated automatically.
+
+(function () {
+    window.__VELOX__.splash = {
+        // Shows the loading progress in the splashscreen, `percent` goes from 0 to 100
+        setProgress: function (percent, text) {
+            return window.__VELOX__.plugin.invoke(
+                "splashscreen", "set_progress", percent, text === undefined ? null : text
+            );
+        },
+
+        // Closes the splashscreen and shows the main window
+        close: function () {
+            return window.__VELOX__.plugin.invoke("splashscreen", "close");
+        },
+
+        // Listens for progress inside of the splashscreen, the callback receives the percent and text
+        onProgress: function (callback) {
+            return window.__VELOX__.event.listen("splashscreen-progress", function (event) {
+                return callback(event.payload.percent, event.payload.text);
+            });
+        },
+    };
+
+    window.__VELOX__.splash.onProgress(function (percent, text) {
+        document.querySelectorAll("[data-velox-splash-progress]").forEach(function (element) {
+            element.value = percent;
+        });
+        if (text !== null) {
+            document.querySelectorAll("[data-velox-splash-text]").forEach(function (element) {
+                element.textContent = text;
+            });
+        }
+    });
+})();
@@ -0,0 +1,53 @@
+// Store API, injected by the store plugin. Values are persisted as JSON on disk.
+//
+// `__VELOX__.store` is the default store, `__VELOX__.store.named(name)` returns
+// another store with the same methods.
+
+(function () {
+    function store(name) {
+        function invoke(command) {
+            const params = Array.prototype.slice.call(arguments, 1);
+            return window.__VELOX__.plugin.invoke.apply(
+                window.__VELOX__.plugin, ["store", command, name].concat(params)
+            );
+        }
+
+        return {
+            // Resolves with the value of a key, null if it does not exist
+            get: function (key) {
+                return invoke("get", key);
+            },
+
+            set: function (key, value) {
+                return invoke("set", key, value === undefined ? null : value);
+            },
+
+            // Resolves with whether the key existed
+            delete: function (key) {
+                return invoke("delete", key);
+            },
+
+            keys: function () {
+                return invoke("keys");
+            },
+
+            clear: function () {
+                return invoke("clear");
+            },
+
+            // Listens for changes from any window or rust, the callback receives the key
+            // and the new value. The key is null if the store was cleared.
+            onChange: function (callback) {
+                return window.__VELOX__.event.listen("store-changed", function (event) {
+                    if (event.payload.store === name) {
+                        return callback(event.payload.key, event.payload.value);
+                    }
+                });
+            },
+
+            named: store,
+        };
+    }
+
+    window.__VELOX__.store = store("default");
+})();
@@ -0,0 +1,70 @@
+// Tray API of velox. Menus use the format of the `menu` section of velox.conf.json.
+
+(function () {
+    function apply(action) {
+        return window.__VELOX__.rpc.call("tray", action);
+    }
+
+    function listen(type, callback) {
+        return window.__VELOX__.event.listen("tray", function (event) {
+            if (event.payload.type === type) {
+                return callback(event.payload);
+            }
+        });
+    }
+
+    window.__VELOX__.tray = {
+        // Changes the icon to a PNG or ICO file in the asset directory
+        setIcon: function (icon) {
+            return apply({ setIcon: icon });
+        },
+
+        // Changes the text which is shown when the mouse hovers over the tray, null removes it
+        setTooltip: function (tooltip) {
+            return apply({ setTooltip: tooltip === undefined ? null : tooltip });
+        },
+
+        setVisible: function (flag) {
+            return apply({ setVisible: flag });
+        },
+
+        // Replaces the menu of the tray, an empty menu removes it
+        setMenu: function (menu) {
+            return apply({ setMenu: menu });
+        },
+
+        // Changes the `title`, `enabled` or `checked` state of the item with an id
+        update: function (id, update) {
+            return apply({ updateMenuItem: { id: id, update: update } });
+        },
+
+        setEnabled: function (id, flag) {
+            return this.update(id, { enabled: flag });
+        },
+
+        setChecked: function (id, flag) {
+            return this.update(id, { checked: flag });
+        },
+
+        // Left clicks and right clicks are only reported on linux
+        onClick: function (callback) {
+            return listen("click", function () {
+                return callback();
+            });
+        },
+
+        onRightClick: function (callback) {
+            return listen("rightClick", function () {
+                return callback();
+            });
+        },
+
+        // Listens for activated menu items, the callback receives the id and the
+        // new state of check items, null for other items.
+        onActivate: function (callback) {
+            return listen("menuActivated", function (payload) {
+                return callback(payload.id, payload.checked);
+            });
+        },
+    };
+})();
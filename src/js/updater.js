@@ -0,0 +1,26 @@
+// Updater API, injected by the updater plugin.
+//
+// Every stage of an update is reported to `onStatus` with a `status` of "checking",
+// "update-available", "up-to-date", "downloading", "verifying", "installing",
+// "restarting" or "error".
+
+(function () {
+    window.__VELOX__.updater = {
+        // Looks for a newer release, the result is reported to `onStatus`
+        check: function () {
+            return window.__VELOX__.plugin.invoke("updater", "check");
+        },
+
+        // Downloads and installs the newest release, then restarts the app
+        install: function () {
+            return window.__VELOX__.plugin.invoke("updater", "install");
+        },
+
+        // Listens for the stages of an update, the callback receives the status object
+        onStatus: function (callback) {
+            return window.__VELOX__.event.listen("updater-status", function (event) {
+                return callback(event.payload);
+            });
+        },
+    };
+})();
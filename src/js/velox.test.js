@@ -22,6 +22,14 @@ let _TESTS = {
             return false;
         }
     },
+    testAddWindowWithHtml: async function() {
+        try {
+            let res = await window.__VELOX__.window.addWindow("test_html", { html: "<h1>test</h1>" });
+            return true;
+        } catch (err) {
+            return false;
+        }
+    },
     testSetTitle: async function() {
         try {
             let res = await window.__VELOX__.window.setTitle("title", "main_window");
@@ -54,10 +62,45 @@ let _TESTS = {
             return false;
         }
     },
+    testGetWindows: async function() {
+        try {
+            let res = await window.__VELOX__.window.getWindows();
+            return Array.isArray(res) && res.includes("main_window");
+        } catch (err) {
+            return false;
+        }
+    },
+    testGetWindowInfo: async function() {
+        try {
+            let res = await window.__VELOX__.window.getWindowInfo("main_window");
+            return res.identifier === "main_window"
+                && res.innerSize.width > 0 && res.innerSize.height > 0
+                && res.outerSize.width >= res.innerSize.width
+                && (res.position === null || typeof res.position.x === "number")
+                && res.visible === true
+                && res.minimized === false;
+        } catch (err) {
+            return false;
+        }
+    },
+    testGetMonitors: async function() {
+        try {
+            let res = await window.__VELOX__.window.getMonitors();
+            return Array.isArray(res) && res.length > 0
+                && res.every(function (monitor) {
+                    return monitor.size.width > 0 && monitor.scaleFactor > 0;
+                });
+        } catch (err) {
+            return false;
+        }
+    },
     testSetMinimize: async function() {
         try {
-            let res = await window.__VELOX__.window.minimize(true, "main_window");
-            return true;
+            await window.__VELOX__.window.minimize(true, "main_window");
+            let minimized = (await window.__VELOX__.window.getWindowInfo("main_window")).minimized;
+            await window.__VELOX__.window.minimize(false, "main_window");
+            let restored = (await window.__VELOX__.window.getWindowInfo("main_window")).minimized;
+            return minimized === true && restored === false;
         } catch (err) {
             return false;
         }
@@ -0,0 +1,164 @@
+// Window API of velox which is not part of the bundled velox.js yet.
+
+(function () {
+    const api = window.__VELOX__.window;
+
+    // Opens a new window. `content` is a url or one of `{url}`, `{html}` and `{asset}`.
+    // `options.parent` makes the window a child of another window, `options.modal`
+    // disables input to the parent and resolves with the result of the child when it closes.
+    api.addWindow = function (title, content, options) {
+        return window.__VELOX__.rpc.call("add_window", title, content, options || {});
+    };
+
+    // Opens a window which is declared in the `windows` section of velox.conf.json
+    api.openWindow = function (windowIdentifier) {
+        return window.__VELOX__.rpc.call("open_window", windowIdentifier);
+    };
+
+    // Closes a window
+    api.closeWindow = function (windowIdentifier) {
+        return window.__VELOX__.rpc.call("close_window", windowIdentifier);
+    };
+
+    // Sets the value that this window returns to its opener if it is a modal window
+    api.setResult = function (result) {
+        return window.__VELOX__.rpc.call("set_result", result, window.__VELOX__.windowIdentifier);
+    };
+
+    // Number of close listeners, rust closes the window itself once the last one is removed
+    let closeListeners = 0;
+
+    // Listens for a close request of this window. Calling `event.preventDefault()`
+    // inside the callback keeps the window open.
+    api.onCloseRequested = function (callback) {
+        const identifier = window.__VELOX__.windowIdentifier;
+        const unlisten = window.__VELOX__.event.listen("close-requested", callback);
+        if (closeListeners++ === 0) {
+            window.__VELOX__.rpc.call("intercept_close", true, identifier);
+        }
+
+        let removed = false;
+        return function () {
+            if (removed) return;
+            removed = true;
+            unlisten();
+            if (--closeListeners === 0) {
+                window.__VELOX__.rpc.call("intercept_close", false, identifier);
+            }
+        };
+    };
+
+    // Sends a message to the window with the given identifier
+    api.postMessage = function (targetIdentifier, payload) {
+        return window.__VELOX__.rpc.call("post_message", targetIdentifier, payload);
+    };
+
+    // Sends a message to all other windows
+    api.broadcast = function (payload) {
+        return window.__VELOX__.rpc.call("post_message", null, payload);
+    };
+
+    // Listens for messages from other windows. The callback receives the payload
+    // and the identifier of the window which sent it.
+    api.onMessage = function (callback) {
+        return window.__VELOX__.event.listen("message", function (event) {
+            return callback(event.payload.payload, event.payload.source);
+        });
+    };
+
+    // Returns identifiers of all open windows
+    api.getWindows = function () {
+        return window.__VELOX__.rpc.call("get_windows");
+    };
+
+    // Returns size, position and state of a window
+    api.getWindowInfo = function (windowIdentifier) {
+        return window.__VELOX__.rpc.call("get_window_info", windowIdentifier);
+    };
+
+    // Returns all monitors which are connected to the system
+    api.getMonitors = function () {
+        return window.__VELOX__.rpc.call("get_monitors");
+    };
+
+    // Moves a window to a monitor, `monitor` is an index into the list of `getMonitors`
+    api.setMonitor = function (monitor, windowIdentifier) {
+        return window.__VELOX__.rpc.call("set_monitor", monitor, windowIdentifier);
+    };
+
+    // Moves this window with the mouse until the left mouse button is released
+    api.startDrag = function () {
+        return window.__VELOX__.rpc.call("start_drag", window.__VELOX__.windowIdentifier);
+    };
+
+    // Resizes this window with the mouse until the left mouse button is released.
+    // `direction` is one of "north", "north-east", "east", "south-east", "south",
+    // "south-west", "west" and "north-west".
+    api.startResize = function (direction) {
+        return window.__VELOX__.rpc.call(
+            "start_resize", direction, window.__VELOX__.windowIdentifier
+        );
+    };
+
+    // Reloads the page of a window, defaults to this window
+    api.reload = function (windowIdentifier) {
+        return window.__VELOX__.rpc.call(
+            "reload", windowIdentifier || window.__VELOX__.windowIdentifier
+        );
+    };
+
+    // Loads a url in a window, defaults to this window
+    api.navigate = function (url, windowIdentifier) {
+        return window.__VELOX__.rpc.call(
+            "navigate", url, windowIdentifier || window.__VELOX__.windowIdentifier
+        );
+    };
+
+    // Goes back in the history of a window, defaults to this window
+    api.goBack = function (windowIdentifier) {
+        return window.__VELOX__.rpc.call(
+            "go_back", windowIdentifier || window.__VELOX__.windowIdentifier
+        );
+    };
+
+    // Goes forward in the history of a window, defaults to this window
+    api.goForward = function (windowIdentifier) {
+        return window.__VELOX__.rpc.call(
+            "go_forward", windowIdentifier || window.__VELOX__.windowIdentifier
+        );
+    };
+
+    // Evaluates a script in another window and resolves with its JSON-serialized result
+    api.eval = function (script, windowIdentifier) {
+        return window.__VELOX__.rpc.call(
+            "eval_with_result", script, windowIdentifier || window.__VELOX__.windowIdentifier
+        );
+    };
+
+    // Sets the icon of a window to a PNG or ICO file in the asset directory, defaults to this window
+    api.setIcon = function (path, windowIdentifier) {
+        return window.__VELOX__.rpc.call(
+            "set_icon", path, windowIdentifier || window.__VELOX__.windowIdentifier
+        );
+    };
+
+    // Zoom factor of this window, kept up to date by the "zoom-changed" event.
+    // The factor outlives reloads, so it is read back from rust. `__VELOX__.rpc` is only
+    // assigned at the end of the injected scripts, so the rpc of wry is used here.
+    api.zoom = 1;
+    if (window.rpc) {
+        window.rpc.call("get_window_info", window.__VELOX__.windowIdentifier).then(function (info) {
+            api.zoom = info.zoom;
+        });
+    }
+    window.__VELOX__.event.listen("zoom-changed", function (event) {
+        api.zoom = event.payload;
+    });
+
+    // Sets the zoom factor of a window, `1` is the default. Defaults to this window.
+    api.setZoom = function (factor, windowIdentifier) {
+        return window.__VELOX__.rpc.call(
+            "set_zoom", factor, windowIdentifier || window.__VELOX__.windowIdentifier
+        );
+    };
+})();
@@ -0,0 +1,28 @@
+// Keyboard shortcuts for zooming a window, only injected if `zoom_hotkeys` is enabled.
+
+(function () {
+    const api = window.__VELOX__.window;
+    const step = 0.1;
+    const min = 0.3;
+    const max = 3;
+
+    window.addEventListener("keydown", function (event) {
+        if (!(event.ctrlKey || event.metaKey) || event.altKey) {
+            return;
+        }
+
+        let factor;
+        if (event.key === "+" || event.key === "=") {
+            factor = Math.min(api.zoom + step, max);
+        } else if (event.key === "-") {
+            factor = Math.max(api.zoom - step, min);
+        } else if (event.key === "0") {
+            factor = 1;
+        } else {
+            return;
+        }
+
+        event.preventDefault();
+        api.setZoom(Math.round(factor * 10) / 10);
+    });
+})();
@@ -9,11 +9,18 @@
 pub mod api;
 pub mod app;
 pub mod assets;
+pub mod cli;
 pub mod config;
+pub mod crash;
 pub mod events;
 pub mod handler;
+pub mod logger;
+pub mod menu;
 pub mod plugin;
 pub mod server;
+pub mod store;
+pub mod tray;
+pub mod updater;
 pub mod window;
 
 pub use crate::api::fs::file_system;
@@ -32,7 +39,7 @@ custom_error! {
     /// Errors returned by velox
     pub Error
     WryError{source: wry::Error} = "{source}",
-    EventLoopClosed{source: wry::application::event_loop::EventLoopClosed<events::Event>} = "{source}",
+    EventLoopClosed{closed: Box<wry::application::event_loop::EventLoopClosed<events::Event>>} = "{closed}",
     TomlError{source: de::Error} = "{source}",
     JSONError{source: serde_json::error::Error} = "{source}",
     CommandError{detail: String} = "{detail}",
@@ -40,10 +47,27 @@ custom_error! {
     SubProcessError{detail: String} = "{detail}",
     IoError{source: io::Error} = "{source}",
     DialogError{detail: String} = "{detail}",
+    ScriptError{detail: String} = "{detail}",
+    ImageError{source: image::ImageError} = "{source}",
+    IconError{source: wry::application::window::BadIcon} = "{source}",
+    PluginError{detail: String} = "{detail}",
+    UpdaterError{detail: String} = "{detail}",
+    StoreError{detail: String} = "{detail}",
+    LogError{detail: String} = "{detail}",
+    TrayError{detail: String} = "{detail}",
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+// the event which could not be sent is boxed, it would make every `Result` as large as an event
+impl From<wry::application::event_loop::EventLoopClosed<events::Event>> for Error {
+    fn from(err: wry::application::event_loop::EventLoopClosed<events::Event>) -> Self {
+        Error::EventLoopClosed {
+            closed: Box::new(err),
+        }
+    }
+}
+
 /// Describes response data that will be sent back to javascript
 pub enum Response<T: Serialize> {
     /// Successful response with result
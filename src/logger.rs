@@ -0,0 +1,272 @@
+//! Logger of velox which implements the `log` facade.
+//!
+//! Every record is printed to stderr and appended to `<data dir>/logs/<app name>.log`,
+//! see `assets::data_dir`. The file is rotated when it grows larger than `max_size`,
+//! older files are kept as `<app name>.log.1`, `<app name>.log.2` and so on.
+//! Javascript logs through `console.*` end up in the same file with the window identifier
+//! as target.
+
+use crate::assets;
+use crate::config::LogConfig;
+use crate::{Error, Result};
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Number of lines which are kept in memory for crash reports
+const RECENT_LINES: usize = 200;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+struct Logger {
+    level: LevelFilter,
+    file: Option<Mutex<LogFile>>,
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl LogFile {
+    fn open(path: PathBuf, max_size: u64, max_files: usize) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn write(&mut self, line: &str) -> Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Renames `app.log` to `app.log.1`, `app.log.1` to `app.log.2` and so on,
+    /// the oldest file is overwritten.
+    fn rotate(&mut self) -> Result<()> {
+        let rotated = |index: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{}", index));
+            PathBuf::from(path)
+        };
+
+        for index in (1..self.max_files).rev() {
+            let from = if index == 1 {
+                self.path.clone()
+            } else {
+                rotated(index - 1)
+            };
+            if from.exists() {
+                fs::rename(&from, rotated(index))?;
+            }
+        }
+        if self.max_files <= 1 {
+            fs::remove_file(&self.path)?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "[{} {:<5} {}] {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        eprint!("{}", line);
+
+        if let Some(file) = &self.file {
+            if let Err(err) = file.lock().unwrap().write(&line) {
+                eprintln!("could not write log file: {}", err);
+            }
+        }
+
+        let mut recent = RECENT.lock().unwrap();
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().file.flush();
+        }
+    }
+}
+
+/// Installs the logger of velox for an app. Fails if a logger was installed before,
+/// for example by the app itself.
+pub fn init(app_name: &str, config: &LogConfig) -> Result<()> {
+    let level = LevelFilter::from_str(&config.level).map_err(|_| Error::LogError {
+        detail: format!("invalid log level: {}", config.level),
+    })?;
+    let file = if config.file {
+        Some(Mutex::new(LogFile::open(
+            log_path(app_name)?,
+            config.max_size,
+            config.max_files,
+        )?))
+    } else {
+        None
+    };
+
+    log::set_boxed_logger(Box::new(Logger { level, file })).map_err(|err| Error::LogError {
+        detail: err.to_string(),
+    })?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Path of the log file of an app.
+pub fn log_path(app_name: &str) -> Result<PathBuf> {
+    let dir = assets::data_dir(app_name)?;
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(dir.join("logs").join(format!("{}.log", name)))
+}
+
+/// Last lines which were logged, oldest first.
+pub fn recent_lines() -> Vec<String> {
+    // called by the panic hook, which must not panic again
+    match RECENT.try_lock() {
+        Ok(recent) => recent.iter().cloned().collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Logs `[level, ...messages]` which javascript passed to `console.*`.
+pub fn log_from_js(window_identifier: &str, params: &[wry::Value]) {
+    let level = match params.first().and_then(|level| level.as_str()) {
+        Some("error") => Level::Error,
+        Some("warn") => Level::Warn,
+        Some("debug") => Level::Debug,
+        Some("trace") => Level::Trace,
+        _ => Level::Info,
+    };
+    let message = params
+        .iter()
+        .skip(1)
+        .map(|param| match param {
+            wry::Value::String(string) => string.clone(),
+            value => value.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    log::log!(target: &format!("js:{}", window_identifier), level, "{}", message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("velox-logs-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("app.log")
+    }
+
+    fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(suffix);
+        PathBuf::from(path)
+    }
+
+    fn read(path: &Path, suffix: &str) -> String {
+        fs::read_to_string(with_suffix(path, suffix)).unwrap_or_default()
+    }
+
+    #[test]
+    fn rotates_when_full() {
+        let path = temp_log("rotate");
+        let mut file = LogFile::open(path.clone(), 10, 3).unwrap();
+
+        file.write("first\n").unwrap();
+        file.write("second\n").unwrap();
+        file.write("third\n").unwrap();
+        file.write("fourth\n").unwrap();
+
+        // the oldest file was overwritten, only max_files files are kept
+        assert_eq!(read(&path, ""), "fourth\n");
+        assert_eq!(read(&path, ".1"), "third\n");
+        assert_eq!(read(&path, ".2"), "second\n");
+        assert!(!with_suffix(&path, ".3").exists());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn keeps_appending_below_max_size() {
+        let path = temp_log("append");
+        LogFile::open(path.clone(), 100, 3)
+            .unwrap()
+            .write("before restart\n")
+            .unwrap();
+
+        // the size of an existing file counts when it is opened again
+        let mut file = LogFile::open(path.clone(), 25, 3).unwrap();
+        file.write("after\n").unwrap();
+        assert_eq!(read(&path, ""), "before restart\nafter\n");
+        file.write("rotated\n").unwrap();
+        assert_eq!(read(&path, ""), "rotated\n");
+        assert_eq!(read(&path, ".1"), "before restart\nafter\n");
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn single_file_is_truncated() {
+        let path = temp_log("single");
+        let mut file = LogFile::open(path.clone(), 5, 1).unwrap();
+
+        file.write("first\n").unwrap();
+        file.write("second\n").unwrap();
+        assert_eq!(read(&path, ""), "second\n");
+        assert!(!with_suffix(&path, ".1").exists());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
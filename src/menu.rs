@@ -0,0 +1,453 @@
+//! Menus of webview windows and the tray.
+//!
+//! A menu bar is a list of submenus, which are declared in the `menu` section of the
+//! velox config file or built in rust. Activating an item with an `id` emits a
+//! `VeloxEvents::MenuActivated` to rust and a `menu` event to the javascript of its window.
+//! The menu of the tray uses the same items, see `tray`.
+//!
+//! # Example
+//! ```json
+//! "menu": [
+//!     {
+//!         "kind": "submenu",
+//!         "title": "File",
+//!         "items": [
+//!             { "id": "open", "title": "Open", "accelerator": "<Ctrl>O" },
+//!             { "kind": "separator" },
+//!             { "id": "autosave", "kind": "check", "title": "Autosave", "checked": true }
+//!         ]
+//!     }
+//! ]
+//! ```
+//!
+//! Accelerators use the syntax of gtk, like `<Ctrl><Shift>S` or `F11`.
+//! Windows and macOS show nested submenus flattened and checked items with a check mark.
+//! Menu bars may only contain submenus, other items can't be shown there on Windows and macOS.
+
+use crate::events::{Event, VeloxEvents};
+use crate::tray::TrayEvent;
+use crate::{Error, Result};
+
+#[cfg(target_os = "linux")]
+use std::cell::Cell;
+#[cfg(not(target_os = "linux"))]
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::rc::Rc;
+#[cfg(not(target_os = "linux"))]
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use wry::application::event_loop::EventLoopProxy;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MenuItemKind {
+    #[default]
+    Item,
+    Check,
+    Separator,
+    Submenu,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MenuItem {
+    /// Identifier which is sent when the item is activated, also used to update the item
+    pub id: Option<String>,
+    pub title: String,
+    pub kind: MenuItemKind,
+    /// Keyboard shortcut, like `<Ctrl>O`
+    pub accelerator: Option<String>,
+    pub enabled: bool,
+    /// Whether a check item is checked
+    pub checked: bool,
+    /// Items of a submenu
+    pub items: Vec<MenuItem>,
+}
+
+impl Default for MenuItem {
+    fn default() -> Self {
+        Self {
+            id: None,
+            title: String::new(),
+            kind: MenuItemKind::Item,
+            accelerator: None,
+            enabled: true,
+            checked: false,
+            items: vec![],
+        }
+    }
+}
+
+impl MenuItem {
+    /// Creates an item which can be activated.
+    pub fn new(id: &str, title: &str) -> Self {
+        Self {
+            id: Some(id.to_string()),
+            title: title.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Creates an item which toggles between checked and unchecked.
+    pub fn check(id: &str, title: &str, checked: bool) -> Self {
+        Self {
+            kind: MenuItemKind::Check,
+            checked,
+            ..Self::new(id, title)
+        }
+    }
+
+    pub fn separator() -> Self {
+        Self {
+            kind: MenuItemKind::Separator,
+            ..Self::default()
+        }
+    }
+
+    pub fn submenu(title: &str, items: Vec<MenuItem>) -> Self {
+        Self {
+            title: title.to_string(),
+            kind: MenuItemKind::Submenu,
+            items,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the keyboard shortcut of the item, like `<Ctrl>O`.
+    pub fn accelerator(mut self, accelerator: &str) -> Self {
+        self.accelerator = Some(accelerator.to_string());
+        self
+    }
+
+    pub fn enabled(mut self, flag: bool) -> Self {
+        self.enabled = flag;
+        self
+    }
+
+    /// Applies an update to this item.
+    pub fn update(&mut self, update: &MenuItemUpdate) {
+        if let Some(title) = &update.title {
+            self.title = title.clone();
+        }
+        if let Some(enabled) = update.enabled {
+            self.enabled = enabled;
+        }
+        if let Some(checked) = update.checked {
+            self.checked = checked;
+        }
+    }
+}
+
+/// Changes of a menu item at runtime, fields which are `None` are left unchanged.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct MenuItemUpdate {
+    pub title: Option<String>,
+    pub enabled: Option<bool>,
+    pub checked: Option<bool>,
+}
+
+/// Finds the item with an id in a menu and its submenus.
+pub fn find_item_mut<'a>(items: &'a mut [MenuItem], id: &str) -> Option<&'a mut MenuItem> {
+    for item in items {
+        if item.id.as_deref() == Some(id) {
+            return Some(item);
+        }
+        if let Some(item) = find_item_mut(&mut item.items, id) {
+            return Some(item);
+        }
+    }
+    None
+}
+
+/// Checks that a menu bar only contains submenus.
+pub fn check_menu_bar(menu: &[MenuItem]) -> Result<()> {
+    match menu.iter().find(|item| item.kind != MenuItemKind::Submenu) {
+        Some(item) => Err(Error::CommandError {
+            detail: format!(
+                "Menu bars can only contain submenus, `{}` is a {:?}",
+                item.title, item.kind
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Owner of a menu, which decides the event that an activated item emits
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MenuSource {
+    /// Menu bar of the window with this identifier
+    Window(String),
+    Tray,
+}
+
+impl MenuSource {
+    pub(crate) fn activated(&self, id: String, checked: Option<bool>) -> Event {
+        match self {
+            MenuSource::Window(window) => Event::VeloxEvent(VeloxEvents::MenuActivated {
+                window: window.clone(),
+                id,
+                checked,
+            }),
+            MenuSource::Tray => {
+                Event::VeloxEvent(VeloxEvents::TrayEvent(TrayEvent::MenuActivated {
+                    id,
+                    checked,
+                }))
+            }
+        }
+    }
+}
+
+/// Shows the title, state and check mark of an item on its gtk widget.
+#[cfg(target_os = "linux")]
+pub(crate) fn update_gtk_item(widget: &gtk::MenuItem, item: &MenuItem, updating: &Rc<Cell<bool>>) {
+    use gtk::prelude::{Cast, CheckMenuItemExt, GtkMenuItemExt, WidgetExt};
+
+    updating.set(true);
+    widget.set_label(&item.title);
+    widget.set_sensitive(item.enabled);
+    if let Ok(check) = widget.clone().downcast::<gtk::CheckMenuItem>() {
+        check.set_active(item.checked);
+    }
+    updating.set(false);
+}
+
+/// What the gtk items of a menu need to emit their activations
+#[cfg(target_os = "linux")]
+pub(crate) struct GtkMenuContext<'a> {
+    pub source: MenuSource,
+    /// Receives the accelerators of the items, tray menus have none
+    pub accel_group: Option<&'a gtk::AccelGroup>,
+    pub event_proxy: &'a EventLoopProxy<Event>,
+    /// Set while items are changed from rust, which must not emit activations
+    pub updating: &'a Rc<Cell<bool>>,
+}
+
+/// Appends menu items to a gtk menu bar or menu, `widgets` receives the items with an id.
+#[cfg(target_os = "linux")]
+pub(crate) fn append_gtk_items<M: gtk::prelude::IsA<gtk::MenuShell>>(
+    shell: &M,
+    items: &[MenuItem],
+    context: &GtkMenuContext,
+    widgets: &mut HashMap<String, gtk::MenuItem>,
+) {
+    use gtk::prelude::*;
+
+    for item in items {
+        let widget: gtk::MenuItem = match item.kind {
+            MenuItemKind::Separator => gtk::SeparatorMenuItem::new().upcast(),
+            MenuItemKind::Check => {
+                let check = gtk::CheckMenuItem::with_label(&item.title);
+                check.set_active(item.checked);
+                check.upcast()
+            }
+            MenuItemKind::Item => gtk::MenuItem::with_label(&item.title),
+            MenuItemKind::Submenu => {
+                let submenu = gtk::Menu::new();
+                append_gtk_items(&submenu, &item.items, context, widgets);
+                let widget = gtk::MenuItem::with_label(&item.title);
+                widget.set_submenu(Some(&submenu));
+                widget
+            }
+        };
+        widget.set_sensitive(item.enabled);
+
+        if let (Some(accelerator), Some(accel_group)) = (&item.accelerator, context.accel_group) {
+            let (key, modifiers) = gtk::accelerator_parse(accelerator);
+            if key == 0 {
+                log::warn!("invalid accelerator: {}", accelerator);
+            } else {
+                widget.add_accelerator(
+                    "activate",
+                    accel_group,
+                    key,
+                    modifiers,
+                    gtk::AccelFlags::VISIBLE,
+                );
+            }
+        }
+
+        if let (Some(id), false) = (&item.id, item.kind == MenuItemKind::Submenu) {
+            let (source, id_, event_proxy, updating) = (
+                context.source.clone(),
+                id.clone(),
+                context.event_proxy.clone(),
+                context.updating.clone(),
+            );
+            widget.connect_activate(move |widget| {
+                if updating.get() {
+                    return;
+                }
+                let checked = widget
+                    .clone()
+                    .downcast::<gtk::CheckMenuItem>()
+                    .ok()
+                    .map(|check| check.get_active());
+
+                let event = source.activated(id_.clone(), checked);
+                if let Err(err) = event_proxy.send_event(event) {
+                    log::error!("{}", err);
+                }
+            });
+            widgets.insert(id.clone(), widget.clone());
+        }
+
+        shell.append(&widget);
+    }
+}
+
+/// Menus which own the ids of tao menu items
+#[cfg(not(target_os = "linux"))]
+static TAO_MENU_IDS: Mutex<BTreeMap<u32, MenuSource>> = Mutex::new(BTreeMap::new());
+
+/// Creates a tao item whose id no other menu item uses. tao derives ids from the titles,
+/// so zero-width spaces are appended to the title until the id is unique.
+#[cfg(not(target_os = "linux"))]
+fn claim_tao_item(mut title: String, source: &MenuSource) -> wry::application::menu::MenuItem {
+    use wry::application::menu::MenuItem as TaoMenuItem;
+
+    let mut claimed = TAO_MENU_IDS.lock().unwrap();
+    loop {
+        let item = TaoMenuItem::new(&title);
+        let id = item.id().0;
+        if !claimed.contains_key(&id) {
+            claimed.insert(id, source.clone());
+            return item;
+        }
+        title.push('\u{200B}');
+    }
+}
+
+/// Frees the ids of the tao items of a menu, before it is rebuilt or its window closed.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn release_tao_items(source: &MenuSource) {
+    TAO_MENU_IDS
+        .lock()
+        .unwrap()
+        .retain(|_, owner| owner != source);
+}
+
+/// Converts menu items to tao, which has no submenus, check items or disabled items.
+/// Items of nested submenus get the titles of their parents as prefix.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn tao_items(
+    items: &[MenuItem],
+    prefix: &str,
+    source: &MenuSource,
+    ids: &mut HashMap<u32, String>,
+) -> Vec<wry::application::menu::MenuItem> {
+    use wry::application::menu::MenuItem as TaoMenuItem;
+
+    let mut tao_items = vec![];
+    for item in items {
+        match item.kind {
+            MenuItemKind::Separator => tao_items.push(TaoMenuItem::Separator),
+            MenuItemKind::Submenu => {
+                let prefix = format!("{}{} > ", prefix, item.title);
+                tao_items.extend(self::tao_items(&item.items, &prefix, source, ids));
+            }
+            MenuItemKind::Item | MenuItemKind::Check => {
+                // the menu of the tray can't be rebuilt, its marks would go stale
+                let mark = if item.kind == MenuItemKind::Check
+                    && item.checked
+                    && *source != MenuSource::Tray
+                {
+                    "\u{2713} "
+                } else {
+                    ""
+                };
+                let title = format!("{}{}{}", mark, prefix, item.title);
+                let mut tao_item = claim_tao_item(title, source);
+                if let Some(accelerator) = &item.accelerator {
+                    tao_item = tao_item.with_accelerators(accelerator);
+                }
+                // disabled items are shown, but their activations are dropped
+                if let (Some(id), true) = (&item.id, item.enabled) {
+                    ids.insert(tao_item.id().0, id.clone());
+                }
+                tao_items.push(tao_item);
+            }
+        }
+    }
+    tao_items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu_bar() -> Vec<MenuItem> {
+        vec![
+            MenuItem::submenu(
+                "File",
+                vec![
+                    MenuItem::new("open", "Open"),
+                    MenuItem::separator(),
+                    MenuItem::submenu("Recent", vec![MenuItem::new("recent-1", "notes.txt")]),
+                ],
+            ),
+            MenuItem::submenu("View", vec![MenuItem::check("dark", "Dark mode", false)]),
+        ]
+    }
+
+    #[test]
+    fn finds_items_in_submenus() {
+        let mut menu = menu_bar();
+
+        assert_eq!(find_item_mut(&mut menu, "open").unwrap().title, "Open");
+        assert_eq!(
+            find_item_mut(&mut menu, "recent-1").unwrap().title,
+            "notes.txt"
+        );
+        assert_eq!(
+            find_item_mut(&mut menu, "dark").unwrap().kind,
+            MenuItemKind::Check
+        );
+        assert!(find_item_mut(&mut menu, "missing").is_none());
+    }
+
+    #[test]
+    fn updates_only_given_fields() {
+        let mut menu = menu_bar();
+        let item = find_item_mut(&mut menu, "dark").unwrap();
+
+        item.update(&MenuItemUpdate {
+            checked: Some(true),
+            ..MenuItemUpdate::default()
+        });
+        assert!(item.checked);
+        assert!(item.enabled);
+        assert_eq!(item.title, "Dark mode");
+
+        item.update(&MenuItemUpdate {
+            title: Some("Night mode".to_string()),
+            enabled: Some(false),
+            checked: None,
+        });
+        assert!(item.checked);
+        assert!(!item.enabled);
+        assert_eq!(item.title, "Night mode");
+
+        // the change is visible through the menu
+        assert_eq!(
+            find_item_mut(&mut menu, "dark").unwrap().title,
+            "Night mode"
+        );
+    }
+
+    #[test]
+    fn menu_bars_only_contain_submenus() {
+        assert!(check_menu_bar(&menu_bar()).is_ok());
+        assert!(check_menu_bar(&[]).is_ok());
+
+        let mut menu = menu_bar();
+        menu.push(MenuItem::new("quit", "Quit"));
+        assert!(check_menu_bar(&menu).is_err());
+        assert!(check_menu_bar(&[MenuItem::separator()]).is_err());
+    }
+}
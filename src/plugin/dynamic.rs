@@ -0,0 +1,385 @@
+//! Plugins which are loaded from shared libraries at runtime.
+//!
+//! A plugin library exports two functions with the C ABI:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub extern "C" fn velox_plugin_api_version() -> u32 {
+//!     1 // VELOX_PLUGIN_API_VERSION
+//! }
+//!
+//! #[no_mangle]
+//! pub extern "C" fn velox_plugin_create() -> VeloxPlugin {
+//!     VeloxPlugin {
+//!         name: b"hello\0".as_ptr() as *const c_char,
+//!         invoke: Some(invoke),
+//!         ..
+//!     }
+//! }
+//! ```
+//!
+//! Commands receive their parameters as a JSON array and answer with a JSON string
+//! of the form `{"ok": value}` or `{"error": "message"}`, or null if the command
+//! does not exist. Strings returned by the plugin are released with its `free_string`.
+//!
+//! Every function of a plugin is called on the thread which runs the event loop.
+//! A relative plugin directory is resolved against the directory of the executable.
+
+use crate::app::App;
+use crate::events::{Event, WindowEvents};
+use crate::plugin::Plugin;
+use crate::{Error, Result};
+
+use std::env;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::io;
+use std::os::raw::{c_char, c_void};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use libloading::{Library, Symbol};
+use wry::application::event_loop::EventLoopProxy;
+
+/// Version of the C ABI, libraries which were built for another version are rejected.
+pub const VELOX_PLUGIN_API_VERSION: u32 = 1;
+
+/// Functions of velox which a plugin can call, passed to its `setup`.
+#[repr(C)]
+pub struct VeloxHost {
+    /// Passed back to every function of the host
+    pub context: *mut c_void,
+    /// Emits an event with a JSON payload to javascript. `window` is the identifier
+    /// of a window or null to emit the event to all windows. Can be called from any thread.
+    pub emit: extern "C" fn(
+        context: *mut c_void,
+        window: *const c_char,
+        event: *const c_char,
+        payload: *const c_char,
+    ),
+}
+
+/// Description of a plugin which is returned by `velox_plugin_create`.
+/// Every function is optional and receives `state` as first argument.
+#[repr(C)]
+pub struct VeloxPlugin {
+    /// Name of the plugin which javascript uses to call its commands
+    pub name: *const c_char,
+    /// Javascript which is injected into every window, may be null
+    pub initialization_script: *const c_char,
+    /// State of the plugin
+    pub state: *mut c_void,
+    pub setup: Option<extern "C" fn(state: *mut c_void, host: *const VeloxHost)>,
+    /// Runs a command, `params` is a JSON array
+    pub invoke: Option<
+        extern "C" fn(
+            state: *mut c_void,
+            command: *const c_char,
+            params: *const c_char,
+        ) -> *mut c_char,
+    >,
+    /// Receives every event of velox as JSON, `source` is null for events of the event loop
+    pub event_received:
+        Option<extern "C" fn(state: *mut c_void, source: *const c_char, event: *const c_char)>,
+    /// Releases a string which was returned by `invoke`
+    pub free_string: Option<extern "C" fn(string: *mut c_char)>,
+    pub exit: Option<extern "C" fn(state: *mut c_void)>,
+    /// Releases `state`, called before the library is unloaded
+    pub destroy: Option<extern "C" fn(state: *mut c_void)>,
+}
+
+/// What `VeloxHost::context` points to. The proxy is not `Sync`, the mutex keeps emits
+/// of different plugin threads apart.
+type HostContext = Mutex<EventLoopProxy<Event>>;
+
+/// A plugin which was loaded from a shared library.
+pub struct DynamicPlugin {
+    name: String,
+    initialization_script: Option<String>,
+    plugin: VeloxPlugin,
+    /// Keeps the proxy which `host.context` points to alive
+    host: Option<(Box<VeloxHost>, Box<HostContext>)>,
+    // dropped last, the functions of `plugin` point into the library
+    _library: Library,
+}
+
+// `Plugin` requires `Send` because plugins are shared as `Arc<Mutex<_>>` with the rpc
+// handlers. Velox calls plugins from the rpc handlers, the event loop and the setup and
+// exit hooks, which all run on the thread of the event loop, and the mutex keeps those
+// calls apart. So `state` and the pointers of `plugin` are never used by two threads,
+// plugins don't need to be thread-safe themselves.
+unsafe impl Send for DynamicPlugin {}
+
+impl DynamicPlugin {
+    /// Loads a plugin from a shared library and checks its ABI version.
+    pub fn load(path: &Path) -> Result<Self> {
+        let library = Library::new(path)?;
+
+        let plugin = unsafe {
+            let version: Symbol<extern "C" fn() -> u32> =
+                library.get(b"velox_plugin_api_version\0")?;
+            check_api_version(version())?;
+
+            let create: Symbol<extern "C" fn() -> VeloxPlugin> =
+                library.get(b"velox_plugin_create\0")?;
+            create()
+        };
+
+        if plugin.name.is_null() {
+            return Err(Error::PluginError {
+                detail: "plugin has no name".to_string(),
+            });
+        }
+
+        Ok(Self {
+            name: unsafe { from_c_str(plugin.name) },
+            initialization_script: if plugin.initialization_script.is_null() {
+                None
+            } else {
+                Some(unsafe { from_c_str(plugin.initialization_script) })
+            },
+            plugin,
+            host: None,
+            _library: library,
+        })
+    }
+}
+
+impl Plugin for DynamicPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn initialization_script(&self) -> Option<String> {
+        self.initialization_script.clone()
+    }
+
+    fn setup(&mut self, _app: &mut App, event_proxy: EventLoopProxy<Event>) -> Result<()> {
+        let mut proxy = Box::new(Mutex::new(event_proxy));
+        let host = Box::new(VeloxHost {
+            context: &mut *proxy as *mut HostContext as *mut c_void,
+            emit: host_emit,
+        });
+
+        if let Some(setup) = self.plugin.setup {
+            setup(self.plugin.state, &*host);
+        }
+        self.host = Some((host, proxy));
+        Ok(())
+    }
+
+    fn event_received(&mut self, source: Option<&str>, event: &Event) {
+        let event_received = match self.plugin.event_received {
+            Some(event_received) => event_received,
+            None => return,
+        };
+
+        // events which can not be serialized, like replies to rust, are not passed on
+        let event = match serde_json::to_string(event)
+            .ok()
+            .and_then(|e| CString::new(e).ok())
+        {
+            Some(event) => event,
+            None => return,
+        };
+        let source = source.and_then(|source| CString::new(source).ok());
+
+        event_received(
+            self.plugin.state,
+            source
+                .as_ref()
+                .map_or(std::ptr::null(), |source| source.as_ptr()),
+            event.as_ptr(),
+        );
+    }
+
+    fn invoke(&mut self, command: &str, params: &[wry::Value]) -> Option<Result<wry::Value>> {
+        let invoke = self.plugin.invoke?;
+        let command = CString::new(command).ok()?;
+        let params = CString::new(wry::Value::from(params.to_vec()).to_string()).ok()?;
+
+        let res = invoke(self.plugin.state, command.as_ptr(), params.as_ptr());
+        if res.is_null() {
+            return None;
+        }
+
+        let json = unsafe { from_c_str(res) };
+        if let Some(free_string) = self.plugin.free_string {
+            free_string(res);
+        }
+
+        Some(parse_response(&json))
+    }
+
+    fn exit(&mut self) {
+        if let Some(exit) = self.plugin.exit {
+            exit(self.plugin.state);
+        }
+    }
+}
+
+impl Drop for DynamicPlugin {
+    fn drop(&mut self) {
+        if let Some(destroy) = self.plugin.destroy {
+            destroy(self.plugin.state);
+        }
+    }
+}
+
+/// Resolves a relative plugin directory against the directory of the executable,
+/// the working directory of an app is not where it is installed.
+fn plugin_dir(dir: &Path) -> io::Result<PathBuf> {
+    if dir.is_absolute() {
+        return Ok(dir.to_path_buf());
+    }
+
+    let exe = env::current_exe()?;
+    let exe_dir = exe
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "executable has no directory"))?;
+    Ok(exe_dir.join(dir))
+}
+
+/// Loads every shared library in a directory as plugin.
+/// Libraries which fail to load are reported and skipped.
+pub fn load_plugins(dir: &Path) -> Vec<DynamicPlugin> {
+    let entries = match plugin_dir(dir).and_then(fs::read_dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::error!("could not read plugin directory {:?}: {}", dir, err);
+            return vec![];
+        }
+    };
+
+    let mut plugins = vec![];
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path.extension().and_then(|ext| ext.to_str()) != Some(env::consts::DLL_EXTENSION) {
+            continue;
+        }
+
+        match DynamicPlugin::load(&path) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(err) => log::error!("could not load plugin {:?}: {}", path, err),
+        }
+    }
+    plugins
+}
+
+/// Rejects libraries which were built for another version of the C ABI.
+fn check_api_version(version: u32) -> Result<()> {
+    if version == VELOX_PLUGIN_API_VERSION {
+        Ok(())
+    } else {
+        Err(Error::PluginError {
+            detail: format!(
+                "plugin uses api version {}, velox supports {}",
+                version, VELOX_PLUGIN_API_VERSION
+            ),
+        })
+    }
+}
+
+/// Converts `{"ok": value}` or `{"error": "message"}` of a plugin to a result.
+fn parse_response(json: &str) -> Result<wry::Value> {
+    let mut response: wry::Value = serde_json::from_str(json)?;
+
+    if let Some(error) = response.get("error") {
+        return Err(Error::PluginError {
+            detail: error
+                .as_str()
+                .map_or_else(|| error.to_string(), String::from),
+        });
+    }
+
+    match response.get_mut("ok") {
+        Some(value) => Ok(value.take()),
+        None => Err(Error::PluginError {
+            detail: format!("invalid response of plugin: {}", json),
+        }),
+    }
+}
+
+unsafe fn from_c_str(string: *const c_char) -> String {
+    CStr::from_ptr(string).to_string_lossy().into_owned()
+}
+
+extern "C" fn host_emit(
+    context: *mut c_void,
+    window: *const c_char,
+    event: *const c_char,
+    payload: *const c_char,
+) {
+    if context.is_null() || event.is_null() {
+        return;
+    }
+
+    let (event_proxy, event, payload, target) = unsafe {
+        (
+            &*(context as *const HostContext),
+            from_c_str(event),
+            if payload.is_null() {
+                wry::Value::Null
+            } else {
+                serde_json::from_str(&from_c_str(payload)).unwrap_or(wry::Value::Null)
+            },
+            if window.is_null() {
+                None
+            } else {
+                Some(from_c_str(window))
+            },
+        )
+    };
+
+    // a plugin thread which panicked while emitting left a usable proxy behind
+    let event_proxy = event_proxy
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Err(err) = event_proxy.send_event(Event::WindowEvent(WindowEvents::Emit {
+        event,
+        payload,
+        target,
+    })) {
+        log::error!("{}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rejects_other_api_versions() {
+        assert!(check_api_version(VELOX_PLUGIN_API_VERSION).is_ok());
+        assert!(check_api_version(0).is_err());
+        assert!(check_api_version(VELOX_PLUGIN_API_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn parses_ok_responses() {
+        assert_eq!(parse_response(r#"{"ok": 42}"#).unwrap(), json!(42));
+        assert_eq!(
+            parse_response(r#"{"ok": {"name": "hello"}}"#).unwrap(),
+            json!({ "name": "hello" })
+        );
+        assert_eq!(parse_response(r#"{"ok": null}"#).unwrap(), wry::Value::Null);
+    }
+
+    #[test]
+    fn parses_error_responses() {
+        let message = |json| match parse_response(json) {
+            Err(Error::PluginError { detail }) => detail,
+            other => panic!("expected a plugin error, got {:?}", other),
+        };
+
+        assert_eq!(message(r#"{"error": "no such file"}"#), "no such file");
+        assert_eq!(message(r#"{"error": {"code": 2}}"#), r#"{"code":2}"#);
+        // an error wins over a value
+        assert_eq!(message(r#"{"ok": 1, "error": "failed"}"#), "failed");
+        assert!(message(r#"{"value": 1}"#).starts_with("invalid response"));
+        assert!(parse_response("not json").is_err());
+    }
+}
@@ -1,40 +1,57 @@
+//! Plugins extend velox with their own commands, javascript and state.
+//!
+//! A plugin is registered with `AppBuilder::plugin` and is called at every stage
+//! of the lifetime of an app. Javascript calls commands of a plugin with
+//! `__VELOX__.plugin.invoke(pluginName, command, ...params)`.
+
+pub mod dynamic;
+pub mod single_instance;
 pub mod splashscreen;
+pub mod store;
+pub mod updater;
+
+use crate::app::App;
+use crate::events::Event;
+use crate::window::WebviewWindow;
+use crate::Result;
+
+use std::sync::{Arc, Mutex};
+
+use wry::application::event_loop::EventLoopProxy;
+
+/// A registered plugin, shared between the event loop and the webview windows.
+pub type PluginHandle = Arc<Mutex<dyn Plugin>>;
+
+/// Lifecycle hooks of a plugin. Every hook except `name` has a default
+/// implementation which does nothing.
+pub trait Plugin: Send {
+    /// Name of the plugin which javascript uses to call its commands
+    fn name(&self) -> &str;
+
+    /// Javascript which is injected into every window before the page loads.
+    fn initialization_script(&self) -> Option<String> {
+        None
+    }
+
+    /// Called once before the main window is created. The app can still be changed,
+    /// `event_proxy` can be kept to send events to the event loop later on.
+    fn setup(&mut self, _app: &mut App, _event_proxy: EventLoopProxy<Event>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after a window was created.
+    fn window_created(&mut self, _window: &WebviewWindow) {}
+
+    /// Called for every event of velox. `source` is the identifier of the window
+    /// if javascript emitted the event, `None` if it was sent to the event loop.
+    fn event_received(&mut self, _source: Option<&str>, _event: &Event) {}
+
+    /// Runs a command which was called from javascript.
+    /// Returns `None` if the plugin has no such command.
+    fn invoke(&mut self, _command: &str, _params: &[wry::Value]) -> Option<Result<wry::Value>> {
+        None
+    }
 
-// use crate::events::Event;
-// use crate::VeloxError;
-// use wry::ApplicationProxy;
-
-// type PluginFunc = Box<dyn FnOnce(&Event, &ApplicationProxy) -> Result<(), VeloxError>>;
-
-// pub struct Plugin {
-//     plugins: Vec<PluginFunc>,
-// }
-
-// impl Default for Plugin {
-//     fn default() -> Self {
-//         Self::new()
-//     }
-// }
-
-// impl Plugin {
-//     pub fn new() -> Self {
-//         Self { plugins: vec![] }
-//     }
-
-//     pub fn add_plugin(&mut self, plugin: PluginFunc) {
-//         self.plugins.push(plugin);
-//     }
-//     pub fn run_plugins(self, event: &Event, app_proxy: &ApplicationProxy) {
-//         for plugin in self.plugins {
-//             plugin(&event, app_proxy).unwrap();
-//         }
-//     }
-// }
-
-// pub fn initialise_plugins() -> Plugin {
-//     let mut plugin = Plugin::new();
-//     // plugin.add_plugin(Box::new(|event, app_proxy| {
-//     //     // splashscreen::show_splashscreen(event, app_proxy)
-//     // }));
-//     plugin
-// }
+    /// Called once before the app exits.
+    fn exit(&mut self) {}
+}
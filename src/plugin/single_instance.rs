@@ -0,0 +1,201 @@
+//! Keeps only one instance of an app running.
+//!
+//! The first instance listens on a per-user unix domain socket. Later launches send
+//! their command-line arguments and working directory to it and exit, the first
+//! instance then emits a `second-instance` event and focuses its main window.
+//! Launches at the same time are serialised with a lock file next to the socket.
+//! Only supported on unix, other platforms always start a new instance.
+
+use crate::app::App;
+#[cfg(unix)]
+use crate::assets;
+use crate::events::Event;
+use crate::plugin::Plugin;
+use crate::Result;
+
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use wry::application::event_loop::EventLoopProxy;
+
+/// Arguments of a later launch of the app.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SecondInstance {
+    /// Command-line arguments including the path of the executable
+    pub args: Vec<String>,
+    /// Working directory of the later launch
+    pub cwd: String,
+}
+
+pub struct SingleInstance {
+    #[cfg(unix)]
+    socket_path: PathBuf,
+    /// Listens for later launches until `setup` moves it to its thread
+    #[cfg(unix)]
+    listener: Option<std::os::unix::net::UnixListener>,
+    /// Whether this instance bound the socket and removes it on exit
+    #[cfg(unix)]
+    bound: bool,
+}
+
+impl SingleInstance {
+    /// Binds the socket of an app, or sends the arguments of this process to the
+    /// instance which owns it. Returns `None` if an instance is running and this
+    /// process should exit, so it must run before the app starts servers or windows.
+    /// If the socket can't be bound the error is logged and the app starts anyway.
+    pub fn claim(app_name: &str) -> Option<Self> {
+        #[cfg(unix)]
+        {
+            use std::io::ErrorKind;
+            use std::os::unix::io::AsRawFd;
+            use std::os::unix::net::{UnixListener, UnixStream};
+
+            let socket_path = socket_path(app_name);
+
+            // launches at the same time check and bind the socket one after another,
+            // the lock is released when `_lock` is dropped
+            let lock = fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(socket_path.with_extension("lock"));
+            let _lock = match lock {
+                Ok(lock) => {
+                    if unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                        log::warn!("could not lock {:?}", socket_path.with_extension("lock"));
+                    }
+                    Some(lock)
+                }
+                Err(err) => {
+                    log::warn!("could not create lock file: {}", err);
+                    None
+                }
+            };
+
+            match UnixStream::connect(&socket_path) {
+                Ok(stream) => match send_instance(stream) {
+                    Ok(()) => return None,
+                    Err(err) => {
+                        log::error!("could not notify the running instance: {}", err);
+                        return Some(Self {
+                            socket_path,
+                            listener: None,
+                            bound: false,
+                        });
+                    }
+                },
+                // nobody listens on a socket which is left over from a crashed instance
+                Err(err) if err.kind() == ErrorKind::ConnectionRefused => {
+                    let _ = fs::remove_file(&socket_path);
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => log::warn!("could not connect to {:?}: {}", socket_path, err),
+            }
+
+            let listener = match UnixListener::bind(&socket_path) {
+                Ok(listener) => Some(listener),
+                Err(err) => {
+                    log::error!("could not listen on {:?}: {}", socket_path, err);
+                    None
+                }
+            };
+
+            Some(Self {
+                socket_path,
+                bound: listener.is_some(),
+                listener,
+            })
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = app_name;
+            Some(Self {})
+        }
+    }
+}
+
+impl Plugin for SingleInstance {
+    fn name(&self) -> &str {
+        "single-instance"
+    }
+
+    #[allow(unused_variables)]
+    fn setup(&mut self, _app: &mut App, event_proxy: EventLoopProxy<Event>) -> Result<()> {
+        #[cfg(unix)]
+        {
+            use crate::events::VeloxEvents;
+            use std::io::{BufRead, BufReader};
+
+            let listener = match self.listener.take() {
+                Some(listener) => listener,
+                None => return Ok(()),
+            };
+
+            std::thread::spawn(move || {
+                for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+                    let mut line = String::new();
+                    if BufReader::new(stream).read_line(&mut line).is_err() {
+                        continue;
+                    }
+
+                    match serde_json::from_str::<SecondInstance>(&line) {
+                        Ok(instance) => {
+                            let event = Event::VeloxEvent(VeloxEvents::SecondInstance {
+                                args: instance.args,
+                                cwd: instance.cwd,
+                            });
+                            if event_proxy.send_event(event).is_err() {
+                                break;
+                            }
+                        }
+                        Err(err) => log::error!("{}", err),
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn exit(&mut self) {
+        // the socket belongs to another instance if it could not be bound
+        #[cfg(unix)]
+        if self.bound {
+            let _ = fs::remove_file(&self.socket_path);
+        }
+    }
+}
+
+/// Path of the socket of an app, inside of the runtime directory of the user.
+#[cfg(unix)]
+fn socket_path(app_name: &str) -> PathBuf {
+    let name = assets::file_name(app_name);
+
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join(format!("{}.sock", name)),
+        None => std::env::temp_dir().join(format!(
+            "{}-{}.sock",
+            name,
+            std::env::var("USER").unwrap_or_default()
+        )),
+    }
+}
+
+/// Sends the arguments of this process to a running instance of the app.
+#[cfg(unix)]
+fn send_instance(mut stream: std::os::unix::net::UnixStream) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let instance = SecondInstance {
+        args: std::env::args().collect(),
+        cwd: std::env::current_dir()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let message = serde_json::to_string(&instance)? + "\n";
+
+    stream.write_all(message.as_bytes())
+}
@@ -1,55 +1,224 @@
+//! Shows a splashscreen until the main window finished loading.
+//! This plugin is registered by `AppBuilder` if a splashscreen is configured.
+
+use crate::api::splashscreen;
 use crate::app::App;
-use crate::events::{Event, VeloxEvents};
-use crate::Result;
+use crate::config::{SplashscreenConfig, WindowConfig};
+use crate::events::{Event, ResourceEvent, VeloxEvents, WindowEvents};
+use crate::plugin::Plugin;
+use crate::window::WindowOptions;
+use crate::{json, Result};
 
-use std::thread::spawn;
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant};
 
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
 use wry::application::event_loop::EventLoopProxy;
 
-/// Shows splashscreen until app finishes loading content
-pub fn show_splashscreen(
+/// Identifier of the splashscreen window
+const SPLASH_WINDOW: &str = "splash_window";
+
+pub struct Splashscreen {
+    config: SplashscreenConfig,
+    event_proxy: Option<EventLoopProxy<Event>>,
+    /// Passes events of the main window to the thread which closes the splashscreen
+    sender: Option<Sender<Event>>,
+}
+
+impl Splashscreen {
+    pub fn new(config: SplashscreenConfig) -> Self {
+        Self {
+            config,
+            event_proxy: None,
+            sender: None,
+        }
+    }
+}
+
+impl Plugin for Splashscreen {
+    fn name(&self) -> &str {
+        "splashscreen"
+    }
+
+    fn initialization_script(&self) -> Option<String> {
+        Some(include_str!("../js/splashscreen.js").to_string())
+    }
+
+    fn setup(&mut self, app: &mut App, event_proxy: EventLoopProxy<Event>) -> Result<()> {
+        // the main window is shown once the app finished loading
+        match app
+            .windows
+            .iter_mut()
+            .find(|window| window.identifier == "main_window")
+        {
+            Some(window) => window.visible = false,
+            None => app.windows.push(WindowConfig {
+                visible: false,
+                ..WindowConfig::new("main_window")
+            }),
+        }
+
+        let (sender, receiver) = unbounded();
+        show_splashscreen(event_proxy.clone(), app, &self.config, receiver)?;
+
+        self.event_proxy = Some(event_proxy);
+        self.sender = Some(sender);
+        Ok(())
+    }
+
+    fn event_received(&mut self, source: Option<&str>, event: &Event) {
+        match event {
+            Event::VeloxEvent(VeloxEvents::SplashscreenProgress { percent, text }) => {
+                if let Some(event_proxy) = &self.event_proxy {
+                    let script = format!(
+                        "window.__VELOX__.event._dispatch(\"splashscreen-progress\", {})",
+                        json!({ "percent": percent, "text": text })
+                    );
+                    let res = event_proxy.send_event(Event::WindowEvent(WindowEvents::Eval {
+                        script,
+                        identifier: SPLASH_WINDOW.to_string(),
+                        reply: None,
+                    }));
+                    if let Err(err) = res {
+                        log::error!("{}", err);
+                    }
+                }
+            }
+
+            Event::VeloxEvent(VeloxEvents::Loaded)
+            | Event::ResourceEvent(ResourceEvent::Error)
+            | Event::VeloxEvent(VeloxEvents::SplashscreenClose) => {
+                // only the main window decides when the app is loaded
+                let from_main_window = source.is_none() || source == Some("main_window");
+                if let (true, Some(sender)) = (from_main_window, &self.sender) {
+                    // the thread has already exited after the splashscreen was closed
+                    let _ = sender.send(event.clone());
+                }
+            }
+
+            Event::WindowEvent(WindowEvents::LoadFailed(identifier))
+                if identifier == "main_window" =>
+            {
+                if let Some(sender) = &self.sender {
+                    let _ = sender.send(event.clone());
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn invoke(&mut self, command: &str, params: &[wry::Value]) -> Option<Result<wry::Value>> {
+        let event_proxy = self.event_proxy.clone()?;
+
+        let res = match command {
+            "set_progress" => set_progress(params, event_proxy),
+            "close" => splashscreen::close(event_proxy),
+            _ => return None,
+        };
+
+        Some(res.map(wry::Value::from))
+    }
+}
+
+/// Parses `[percent, text]` from javascript and shows them in the splashscreen.
+fn set_progress(params: &[wry::Value], event_proxy: EventLoopProxy<Event>) -> Result<bool> {
+    let percent: f64 = serde_json::from_value(params.first().cloned().unwrap_or_default())?;
+    let text = params
+        .get(1)
+        .and_then(|text| text.as_str())
+        .map(String::from);
+
+    splashscreen::set_progress(percent, text, event_proxy)
+}
+
+/// Shows splashscreen until app finishes loading content.
+/// The main window is shown anyway if its page fails to load or takes longer than the timeout.
+/// Scripts or stylesheets which fail to load are only logged.
+fn show_splashscreen(
     event_proxy: EventLoopProxy<Event>,
-    app_config: App,
+    app: &App,
+    splashscreen: &SplashscreenConfig,
     receiver: Receiver<Event>,
 ) -> Result<()> {
-    use crate::events::WindowEvents;
-
     event_proxy.send_event(Event::WindowEvent(WindowEvents::AddWindow {
-        identifier: "splash_window".to_string(),
-        window_title: app_config.name,
-        content: app_config.splashscreen.unwrap(),
+        identifier: SPLASH_WINDOW.to_string(),
+        window_title: app.name.clone(),
+        content: splashscreen.content.clone(),
+        options: Box::new(WindowOptions {
+            width: splashscreen.width,
+            height: splashscreen.height,
+            decorations: Some(splashscreen.decorations),
+            ..WindowOptions::default()
+        }),
+        reply: None,
     }))?;
 
+    let error_url = splashscreen
+        .error_content
+        .as_ref()
+        .map(|content| content.to_url(&app.url));
+    let started = Instant::now();
+    let mut deadline = Some(started + Duration::from_millis(splashscreen.timeout));
+    let min_duration = Duration::from_millis(splashscreen.min_duration);
+    let manual_close = splashscreen.manual_close;
+
     // spawn new thread and receive event from main window
-    spawn(move || loop {
-        // block this thread until main window finishes loading
-        if let Ok(event) = receiver.recv() {
+    spawn(move || {
+        // block this thread until main window finishes loading, fails or times out
+        let failed = loop {
+            let event = match deadline {
+                Some(deadline) => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
             match event {
-                Event::VeloxEvent(velox_events) => match velox_events {
-                    VeloxEvents::Initialised => {}
-                    VeloxEvents::Loaded => {
-                        // Close splash screen window
-                        event_proxy
-                            .send_event(Event::WindowEvent(WindowEvents::CloseWindow(
-                                "splash_window".to_string(),
-                            )))
-                            .unwrap();
-
-                        // Show main window
-                        event_proxy
-                            .send_event(Event::WindowEvent(WindowEvents::ShowWindow(
-                                "main_window".to_string(),
-                            )))
-                            .unwrap();
-                        break;
-                    }
-                },
-                Event::ResourceEvent(_event) => {}
-                Event::NetworkEvent(_event) => {}
-                _ => {}
+                Ok(Event::VeloxEvent(VeloxEvents::Loaded)) if !manual_close => break false,
+                Ok(Event::VeloxEvent(VeloxEvents::SplashscreenClose)) => break false,
+                Ok(Event::WindowEvent(WindowEvents::LoadFailed(_))) => break true,
+                Ok(Event::ResourceEvent(ResourceEvent::Error)) => {
+                    log::warn!("a script or stylesheet of the main window failed to load");
+                }
+                Ok(_) => {}
+                // the app is still preparing itself and closes the splashscreen later
+                Err(RecvTimeoutError::Timeout) if manual_close => {
+                    log::warn!("splashscreen was not closed in time");
+                    deadline = None;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    log::warn!("main window did not load in time");
+                    break true;
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        };
+
+        // keep the splashscreen for its minimum duration
+        let elapsed = started.elapsed();
+        if elapsed < min_duration {
+            sleep(min_duration - elapsed);
+        }
+
+        let send = |event| {
+            if let Err(err) = event_proxy.send_event(Event::WindowEvent(event)) {
+                log::error!("{}", err);
             }
+        };
+
+        if let (true, Some(url)) = (failed, error_url) {
+            send(WindowEvents::Navigate {
+                url,
+                identifier: "main_window".to_string(),
+            });
         }
+
+        // Close splash screen window
+        send(WindowEvents::CloseWindow(SPLASH_WINDOW.to_string()));
+
+        // Show main window
+        send(WindowEvents::ShowWindow("main_window".to_string()));
     });
     Ok(())
 }
@@ -0,0 +1,138 @@
+//! Persistent key-value stores for javascript and rust.
+//! This plugin is always registered by `AppBuilder`.
+//!
+//! Every change is emitted to all windows as `store-changed` event with the
+//! name of the store, the key and the new value. Clearing a store emits a null key.
+
+use crate::app::App;
+use crate::events::{Event, Reply, VeloxEvents, WindowEvents};
+use crate::plugin::Plugin;
+use crate::store::{Store, StoreAction};
+use crate::{json, Error, Result};
+
+use std::collections::HashMap;
+
+use wry::application::event_loop::EventLoopProxy;
+
+pub struct StorePlugin {
+    app_name: String,
+    /// Stores which were opened so far by name
+    stores: HashMap<String, Store>,
+    event_proxy: Option<EventLoopProxy<Event>>,
+}
+
+impl StorePlugin {
+    pub fn new(app_name: &str) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            stores: HashMap::new(),
+            event_proxy: None,
+        }
+    }
+
+    /// Runs an action on a store and emits the change to all windows.
+    fn run(&mut self, name: &str, action: StoreAction) -> Result<wry::Value> {
+        if !self.stores.contains_key(name) {
+            let store = Store::open(&self.app_name, name)?;
+            self.stores.insert(name.to_string(), store);
+        }
+
+        let change = match &action {
+            StoreAction::Set { key, value } => {
+                Some((wry::Value::from(key.as_str()), value.clone()))
+            }
+            StoreAction::Delete { key } => Some((wry::Value::from(key.as_str()), wry::Value::Null)),
+            StoreAction::Clear => Some((wry::Value::Null, wry::Value::Null)),
+            _ => None,
+        };
+
+        let res = self.stores.get_mut(name).unwrap().apply(action)?;
+
+        // deleting a missing key changes nothing
+        if let (Some((key, value)), Some(event_proxy), false) =
+            (change, &self.event_proxy, res == wry::Value::Bool(false))
+        {
+            event_proxy.send_event(Event::WindowEvent(WindowEvents::Emit {
+                event: "store-changed".to_string(),
+                payload: json!({ "store": name, "key": key, "value": value }),
+                target: None,
+            }))?;
+        }
+        Ok(res)
+    }
+}
+
+impl Plugin for StorePlugin {
+    fn name(&self) -> &str {
+        "store"
+    }
+
+    fn initialization_script(&self) -> Option<String> {
+        Some(include_str!("../js/store.js").to_string())
+    }
+
+    fn setup(&mut self, _app: &mut App, event_proxy: EventLoopProxy<Event>) -> Result<()> {
+        self.event_proxy = Some(event_proxy);
+        Ok(())
+    }
+
+    fn event_received(&mut self, source: Option<&str>, event: &Event) {
+        // only rust changes stores through the event loop
+        if let (
+            None,
+            Event::VeloxEvent(VeloxEvents::Store {
+                store,
+                action,
+                reply,
+            }),
+        ) = (source, event)
+        {
+            let res = self.run(store, action.clone());
+            match reply {
+                Some(Reply::Channel(sender)) => {
+                    // the receiver may have been dropped
+                    let _ = sender.send(res);
+                }
+                _ => {
+                    if let Err(err) = res {
+                        log::error!("{}", err);
+                    }
+                }
+            }
+        }
+    }
+
+    fn invoke(&mut self, command: &str, params: &[wry::Value]) -> Option<Result<wry::Value>> {
+        let action = match command {
+            "get" => key(params).map(|key| StoreAction::Get { key }),
+            "set" => key(params).map(|key| StoreAction::Set {
+                key,
+                value: params.get(2).cloned().unwrap_or_default(),
+            }),
+            "delete" => key(params).map(|key| StoreAction::Delete { key }),
+            "keys" => Ok(StoreAction::Keys),
+            "clear" => Ok(StoreAction::Clear),
+            _ => return None,
+        };
+
+        let name = params.first().and_then(|name| name.as_str());
+        Some(match (name, action) {
+            (Some(name), Ok(action)) => self.run(name, action),
+            (None, _) => Err(Error::StoreError {
+                detail: "missing name of the store".to_string(),
+            }),
+            (_, Err(err)) => Err(err),
+        })
+    }
+}
+
+/// Parses the key of `[store, key, ..]` from javascript.
+fn key(params: &[wry::Value]) -> Result<String> {
+    params
+        .get(1)
+        .and_then(|key| key.as_str())
+        .map(String::from)
+        .ok_or_else(|| Error::StoreError {
+            detail: "key must be a string".to_string(),
+        })
+}
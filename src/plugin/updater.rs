@@ -0,0 +1,211 @@
+//! Checks for updates and installs them while the app is running.
+//! This plugin is registered by `AppBuilder` if the `updater` section of the config is active.
+//!
+//! Every stage is emitted to all windows as `updater-status` event with a `status` of
+//! `checking`, `update-available`, `up-to-date`, `downloading`, `verifying`,
+//! `installing`, `restarting` or `error`.
+
+use crate::app::App;
+use crate::config::UpdaterConfig;
+use crate::events::{Event, VeloxEvents, WindowEvents};
+use crate::plugin::Plugin;
+use crate::updater::{Manifest, Updater};
+use crate::{json, Error, Result};
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+
+use wry::application::event_loop::EventLoopProxy;
+
+pub struct UpdaterPlugin {
+    config: UpdaterConfig,
+    version: String,
+    state: Option<Arc<State>>,
+}
+
+/// Shared with the threads which check for and install updates
+struct State {
+    updater: Updater,
+    event_proxy: EventLoopProxy<Event>,
+    /// Newest release which was found by the last check
+    manifest: Mutex<Option<Manifest>>,
+    /// Whether a check or an installation is running
+    busy: AtomicBool,
+    /// Whether the new executable is started after the app exited
+    relaunch: AtomicBool,
+}
+
+impl UpdaterPlugin {
+    pub fn new(config: UpdaterConfig, version: String) -> Self {
+        Self {
+            config,
+            version,
+            state: None,
+        }
+    }
+}
+
+impl Plugin for UpdaterPlugin {
+    fn name(&self) -> &str {
+        "updater"
+    }
+
+    fn initialization_script(&self) -> Option<String> {
+        Some(include_str!("../js/updater.js").to_string())
+    }
+
+    fn setup(&mut self, app: &mut App, event_proxy: EventLoopProxy<Event>) -> Result<()> {
+        let updater = Updater::new(&self.config, &self.version)?
+            .with_asset_dir(PathBuf::from(&app.asset_dir));
+        let state = Arc::new(State {
+            updater,
+            event_proxy,
+            manifest: Mutex::new(None),
+            busy: AtomicBool::new(false),
+            relaunch: AtomicBool::new(false),
+        });
+
+        if self.config.check_on_startup {
+            start(state.clone(), false)?;
+        }
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn event_received(&mut self, source: Option<&str>, event: &Event) {
+        // only rust can start an update through the event loop
+        if source.is_some() {
+            return;
+        }
+
+        let install = match event {
+            Event::VeloxEvent(VeloxEvents::CheckUpdate) => false,
+            Event::VeloxEvent(VeloxEvents::InstallUpdate) => true,
+            _ => return,
+        };
+        if let Some(state) = &self.state {
+            if let Err(err) = start(state.clone(), install) {
+                log::error!("{}", err);
+            }
+        }
+    }
+
+    fn invoke(&mut self, command: &str, _params: &[wry::Value]) -> Option<Result<wry::Value>> {
+        let state = self.state.clone()?;
+
+        let res = match command {
+            "check" => start(state, false),
+            "install" => start(state, true),
+            _ => return None,
+        };
+        Some(res.map(|_| wry::Value::from(true)))
+    }
+
+    fn exit(&mut self) {
+        let state = match &self.state {
+            Some(state) if state.relaunch.load(Ordering::SeqCst) => state,
+            _ => return,
+        };
+
+        let target: PathBuf = state.updater.target().to_path_buf();
+        if let Err(err) = std::process::Command::new(target)
+            .args(std::env::args().skip(1))
+            .spawn()
+        {
+            log::error!("{}", err);
+        }
+    }
+}
+
+/// Checks for an update in a new thread and installs it if `install` is true.
+fn start(state: Arc<State>, install: bool) -> Result<()> {
+    if state.busy.swap(true, Ordering::SeqCst) {
+        return Err(Error::UpdaterError {
+            detail: "an update is already running".to_string(),
+        });
+    }
+
+    spawn(move || {
+        let res = if install {
+            install_update(&state)
+        } else {
+            check_update(&state).map(|_| ())
+        };
+        if let Err(err) = res {
+            emit_status(
+                &state,
+                json!({ "status": "error", "error": err.to_string() }),
+            );
+        }
+        state.busy.store(false, Ordering::SeqCst);
+    });
+    Ok(())
+}
+
+fn check_update(state: &State) -> Result<Option<Manifest>> {
+    emit_status(state, json!({ "status": "checking" }));
+
+    let manifest = state.updater.check()?;
+    match &manifest {
+        Some(manifest) => emit_status(
+            state,
+            json!({ "status": "update-available", "manifest": manifest }),
+        ),
+        None => emit_status(state, json!({ "status": "up-to-date" })),
+    }
+
+    *state.manifest.lock().unwrap() = manifest.clone();
+    Ok(manifest)
+}
+
+fn install_update(state: &State) -> Result<()> {
+    let cached = state.manifest.lock().unwrap().clone();
+    let manifest = match cached {
+        Some(manifest) => manifest,
+        None => match check_update(state)? {
+            Some(manifest) => manifest,
+            None => return Ok(()),
+        },
+    };
+
+    // progress is only emitted when the percentage changes
+    let mut last_percent = None;
+    let archive = state.updater.download(&manifest, |downloaded, total| {
+        let percent = total.map(|total| downloaded * 100 / total.max(1));
+        if percent.is_none() || percent != last_percent {
+            last_percent = percent;
+            emit_status(
+                state,
+                json!({ "status": "downloading", "downloaded": downloaded, "total": total }),
+            );
+        }
+    })?;
+
+    emit_status(state, json!({ "status": "verifying" }));
+    state.updater.verify(&archive, &manifest.signature)?;
+
+    emit_status(state, json!({ "status": "installing" }));
+    state.updater.install(&archive)?;
+
+    emit_status(state, json!({ "status": "restarting" }));
+    state.relaunch.store(true, Ordering::SeqCst);
+    state
+        .event_proxy
+        .send_event(Event::VeloxEvent(VeloxEvents::Exit))?;
+    Ok(())
+}
+
+fn emit_status(state: &State, payload: wry::Value) {
+    let res = state
+        .event_proxy
+        .send_event(Event::WindowEvent(WindowEvents::Emit {
+            event: "updater-status".to_string(),
+            payload,
+            target: None,
+        }));
+    if let Err(err) = res {
+        log::error!("{}", err);
+    }
+}
@@ -17,12 +17,12 @@ fn handle_req(req: Request, config: &config::VeloxConfig) {
 
 pub fn spawn_server(addrs: &str, config: config::VeloxConfig) {
     let server = tiny_http::Server::http(addrs).unwrap();
-    println!("Server spawned on url: {:?}", addrs);
+    log::info!("asset server listening on {}", addrs);
     spawn(move || loop {
         match server.recv() {
             Ok(req) => handle_req(req, &config),
             Err(e) => {
-                println!("error: {}", e);
+                log::error!("asset server stopped: {}", e);
                 break;
             }
         };
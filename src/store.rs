@@ -0,0 +1,219 @@
+//! Persistent key-value stores of an app.
+//!
+//! Every store is a JSON file in `<data dir>/stores/<name>.json`, see `assets::data_dir`.
+//! Changes are written to a temporary file which is flushed to disk and renamed over
+//! the store, so a store is never left half written. A change which could not be saved
+//! is not applied.
+
+use crate::assets;
+use crate::{Error, Result};
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the store which `__VELOX__.store` uses
+pub const DEFAULT_STORE: &str = "default";
+
+/// Changes or reads a store, sent with `VeloxEvents::Store`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreAction {
+    Get { key: String },
+    Set { key: String, value: wry::Value },
+    Delete { key: String },
+    Keys,
+    Clear,
+}
+
+#[derive(Debug, Clone)]
+pub struct Store {
+    path: PathBuf,
+    values: serde_json::Map<String, wry::Value>,
+}
+
+impl Store {
+    /// Opens a named store of an app, a store which does not exist yet is empty.
+    pub fn open(app_name: &str, name: &str) -> Result<Self> {
+        Self::open_path(store_path(app_name, name)?)
+    }
+
+    /// Opens a store at any path.
+    pub fn open_path(path: PathBuf) -> Result<Self> {
+        let values = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => serde_json::Map::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { path, values })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&wry::Value> {
+        self.values.get(key)
+    }
+
+    /// Sets a value and saves the store.
+    pub fn set(&mut self, key: &str, value: wry::Value) -> Result<()> {
+        let mut values = self.values.clone();
+        values.insert(key.to_string(), value);
+        self.commit(values)
+    }
+
+    /// Deletes a value and saves the store. Returns whether the key existed.
+    pub fn delete(&mut self, key: &str) -> Result<bool> {
+        if !self.values.contains_key(key) {
+            return Ok(false);
+        }
+
+        let mut values = self.values.clone();
+        values.remove(key);
+        self.commit(values)?;
+        Ok(true)
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+
+    /// Deletes all values and saves the store.
+    pub fn clear(&mut self) -> Result<()> {
+        self.commit(serde_json::Map::new())
+    }
+
+    /// Runs an action and returns its result as JSON.
+    pub fn apply(&mut self, action: StoreAction) -> Result<wry::Value> {
+        match action {
+            StoreAction::Get { key } => Ok(self.get(&key).cloned().unwrap_or_default()),
+            StoreAction::Set { key, value } => self.set(&key, value).map(|_| true.into()),
+            StoreAction::Delete { key } => self.delete(&key).map(wry::Value::from),
+            StoreAction::Keys => Ok(self.keys().into()),
+            StoreAction::Clear => self.clear().map(|_| true.into()),
+        }
+    }
+
+    /// Saves `values` and replaces the values in memory once they were saved.
+    fn commit(&mut self, values: serde_json::Map<String, wry::Value>) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let temp = self.path.with_extension("json.tmp");
+        let mut file = File::create(&temp)?;
+        file.write_all(&serde_json::to_vec_pretty(&values)?)?;
+        // the rename must not reach the disk before the contents
+        file.sync_all()?;
+        fs::rename(&temp, &self.path)?;
+
+        self.values = values;
+        Ok(())
+    }
+}
+
+/// Path of a named store of an app. Names may only contain letters, digits, `-` and `_`.
+pub fn store_path(app_name: &str, name: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(Error::StoreError {
+            detail: format!("invalid store name: {:?}", name),
+        });
+    }
+
+    Ok(assets::data_dir(app_name)?
+        .join("stores")
+        .join(format!("{}.json", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_store(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("velox-store-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("stores").join("default.json")
+    }
+
+    #[test]
+    fn missing_store_is_empty() {
+        let store = Store::open_path(temp_store("missing")).unwrap();
+        assert!(store.keys().is_empty());
+        assert_eq!(store.get("theme"), None);
+    }
+
+    #[test]
+    fn values_persist() {
+        let path = temp_store("persist");
+        let mut store = Store::open_path(path.clone()).unwrap();
+
+        store.set("theme", json!("dark")).unwrap();
+        store.set("size", json!({ "width": 800 })).unwrap();
+        assert_eq!(store.get("theme"), Some(&json!("dark")));
+
+        let mut reopened = Store::open_path(path.clone()).unwrap();
+        assert_eq!(reopened.get("size"), Some(&json!({ "width": 800 })));
+        let mut keys = reopened.keys();
+        keys.sort();
+        assert_eq!(keys, vec!["size", "theme"]);
+
+        assert!(reopened.delete("theme").unwrap());
+        assert!(!reopened.delete("theme").unwrap());
+        assert_eq!(Store::open_path(path.clone()).unwrap().keys(), vec!["size"]);
+
+        reopened.clear().unwrap();
+        assert!(Store::open_path(path.clone()).unwrap().keys().is_empty());
+        assert!(!path.with_extension("json.tmp").exists());
+
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn apply_answers_with_json() {
+        let path = temp_store("apply");
+        let mut store = Store::open_path(path.clone()).unwrap();
+
+        let set = StoreAction::Set {
+            key: "count".to_string(),
+            value: json!(1),
+        };
+        assert_eq!(store.apply(set).unwrap(), json!(true));
+        let get = StoreAction::Get {
+            key: "count".to_string(),
+        };
+        assert_eq!(store.apply(get).unwrap(), json!(1));
+        assert_eq!(store.apply(StoreAction::Keys).unwrap(), json!(["count"]));
+        let missing = StoreAction::Get {
+            key: "missing".to_string(),
+        };
+        assert_eq!(store.apply(missing).unwrap(), wry::Value::Null);
+
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn failed_save_keeps_values() {
+        let path = temp_store("failed");
+        let mut store = Store::open_path(path.clone()).unwrap();
+        store.set("theme", json!("dark")).unwrap();
+
+        // a directory in place of the temporary file makes saving fail
+        fs::create_dir_all(path.with_extension("json.tmp")).unwrap();
+        assert!(store.set("theme", json!("light")).is_err());
+        assert!(store.clear().is_err());
+        assert_eq!(store.get("theme"), Some(&json!("dark")));
+
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn store_names_are_checked() {
+        assert!(store_path("app", "").is_err());
+        assert!(store_path("app", "../secrets").is_err());
+        assert!(store_path("app", "a/b").is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! Icon of an app in the system tray.
+//!
+//! The tray is declared in the `tray` section of the velox config file or built in rust
+//! with `AppBuilder::tray`. Clicks and activated items of its menu emit a
+//! `VeloxEvents::TrayEvent` to rust and a `tray` event to the javascript of all windows.
+//! Rust changes the tray through `api::tray`, javascript through `__VELOX__.tray`.
+//!
+//! # Example
+//! ```json
+//! "tray": {
+//!     "icon": "tray.png",
+//!     "tooltip": "Minimal",
+//!     "hide_on_close": true,
+//!     "menu": [
+//!         { "id": "show", "title": "Show" },
+//!         { "kind": "separator" },
+//!         { "id": "quit", "title": "Quit" }
+//!     ]
+//! }
+//! ```
+//!
+//! On linux a left click emits `click`, a right click shows the menu and emits `rightClick`.
+//! Windows and macOS show the menu on every click and don't emit clicks, their tray has no
+//! tooltip and can't be changed after it was created. Check items toggle their state there,
+//! but show no check mark.
+
+use crate::assets;
+use crate::config::TrayConfig;
+use crate::events::Event;
+use crate::menu::{self, MenuItem, MenuItemUpdate};
+use crate::{Error, Result};
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use wry::application::event_loop::{EventLoopProxy, EventLoopWindowTarget};
+
+/// Changes the tray, sent with `VeloxEvents::Tray`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum TrayAction {
+    /// Path of the icon, relative to the asset directory
+    SetIcon(String),
+    SetTooltip(Option<String>),
+    SetVisible(bool),
+    SetMenu(Vec<MenuItem>),
+    UpdateMenuItem {
+        id: String,
+        update: MenuItemUpdate,
+    },
+}
+
+/// What happened to the tray, sent with `VeloxEvents::TrayEvent`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TrayEvent {
+    Click,
+    RightClick,
+    MenuActivated {
+        /// Id of the menu item
+        id: String,
+        /// New state of a check item
+        checked: Option<bool>,
+    },
+}
+
+pub struct Tray {
+    menu: Vec<MenuItem>,
+    #[cfg(target_os = "linux")]
+    status_icon: glib::Object,
+    #[cfg(target_os = "linux")]
+    gtk_menu: gtk::Menu,
+    #[cfg(target_os = "linux")]
+    menu_widgets: HashMap<String, gtk::MenuItem>,
+    #[cfg(target_os = "linux")]
+    menu_updating: std::rc::Rc<std::cell::Cell<bool>>,
+    #[cfg(target_os = "linux")]
+    event_proxy: EventLoopProxy<Event>,
+    #[cfg(not(target_os = "linux"))]
+    _system_tray: wry::application::platform::system_tray::SystemTray,
+    #[cfg(not(target_os = "linux"))]
+    menu_ids: HashMap<u32, String>,
+}
+
+impl Tray {
+    /// Shows the tray with an icon file, the icon of `config` is resolved by the app.
+    /// `event_proxy` receives clicks and activated menu items.
+    #[cfg(target_os = "linux")]
+    pub fn new(
+        config: &TrayConfig,
+        icon: &Path,
+        _event_loop_target: &EventLoopWindowTarget<Event>,
+        event_proxy: EventLoopProxy<Event>,
+    ) -> Result<Self> {
+        use glib::translate::from_glib_full;
+        use glib::{ObjectExt, ObjectType};
+
+        // gtk-rs has no bindings for the deprecated, but still working, GtkStatusIcon
+        let status_icon: glib::Object = unsafe {
+            from_glib_full(gtk_sys::gtk_status_icon_new() as *mut glib::gobject_sys::GObject)
+        };
+        let gtk_menu = gtk::Menu::new();
+
+        let proxy = event_proxy.clone();
+        status_icon
+            .connect_local("activate", false, move |_| {
+                send_event(&proxy, TrayEvent::Click);
+                None
+            })
+            .map_err(tray_error)?;
+
+        let (proxy, popup_menu, icon_ptr) = (
+            event_proxy.clone(),
+            gtk_menu.clone(),
+            status_icon.as_ptr() as *mut gtk_sys::GtkStatusIcon,
+        );
+        status_icon
+            .connect_local("popup-menu", false, move |values| {
+                use glib::translate::ToGlibPtr;
+                use gtk::prelude::ContainerExt;
+
+                if !popup_menu.get_children().is_empty() {
+                    let button = values.get(1).and_then(|value| value.get_some().ok());
+                    let time = values.get(2).and_then(|value| value.get_some().ok());
+                    unsafe {
+                        gtk_sys::gtk_menu_popup(
+                            popup_menu.to_glib_none().0,
+                            std::ptr::null_mut(),
+                            std::ptr::null_mut(),
+                            Some(position_menu),
+                            icon_ptr as glib::glib_sys::gpointer,
+                            button.unwrap_or(0),
+                            time.unwrap_or(0),
+                        );
+                    }
+                }
+                send_event(&proxy, TrayEvent::RightClick);
+                None
+            })
+            .map_err(tray_error)?;
+
+        let mut tray = Self {
+            menu: vec![],
+            status_icon,
+            gtk_menu,
+            menu_widgets: HashMap::new(),
+            menu_updating: Default::default(),
+            event_proxy,
+        };
+        tray.set_icon(icon)?;
+        tray.set_tooltip(config.tooltip.as_deref())?;
+        tray.set_menu(config.menu.clone())?;
+        Ok(tray)
+    }
+
+    /// Shows the tray with an icon file, the icon of `config` is resolved by the app.
+    /// `event_proxy` receives clicks and activated menu items.
+    #[cfg(not(target_os = "linux"))]
+    pub fn new(
+        config: &TrayConfig,
+        icon: &Path,
+        event_loop_target: &EventLoopWindowTarget<Event>,
+        _event_proxy: EventLoopProxy<Event>,
+    ) -> Result<Self> {
+        use wry::application::platform::system_tray::SystemTrayBuilder;
+
+        let mut menu_ids = HashMap::new();
+        let items = menu::tao_items(&config.menu, "", &menu::MenuSource::Tray, &mut menu_ids);
+        let system_tray = SystemTrayBuilder::new(std::fs::read(icon)?, items)
+            .build(event_loop_target)
+            .map_err(tray_error)?;
+
+        Ok(Self {
+            menu: config.menu.clone(),
+            _system_tray: system_tray,
+            menu_ids,
+        })
+    }
+
+    /// Changes the icon to a PNG or ICO file.
+    #[cfg(target_os = "linux")]
+    pub fn set_icon(&mut self, icon: &Path) -> Result<()> {
+        if !icon.is_file() {
+            return Err(Error::TrayError {
+                detail: format!("Tray icon `{}` does not exist", icon.display()),
+            });
+        }
+
+        let icon = c_string(&icon.to_string_lossy())?;
+        unsafe { gtk_sys::gtk_status_icon_set_from_file(self.status_icon_ptr(), icon.as_ptr()) };
+        Ok(())
+    }
+
+    /// Changes the text which is shown when the mouse hovers over the tray, `None` removes it.
+    #[cfg(target_os = "linux")]
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) -> Result<()> {
+        let tooltip = tooltip.map(c_string).transpose()?;
+        let tooltip_ptr = tooltip
+            .as_ref()
+            .map_or(std::ptr::null(), |tooltip| tooltip.as_ptr());
+        unsafe { gtk_sys::gtk_status_icon_set_tooltip_text(self.status_icon_ptr(), tooltip_ptr) };
+        Ok(())
+    }
+
+    /// Shows or hides the tray.
+    #[cfg(target_os = "linux")]
+    pub fn set_visible(&mut self, visible: bool) -> Result<()> {
+        unsafe { gtk_sys::gtk_status_icon_set_visible(self.status_icon_ptr(), visible as i32) };
+        Ok(())
+    }
+
+    /// Replaces the menu of the tray, an empty menu removes it.
+    #[cfg(target_os = "linux")]
+    pub fn set_menu(&mut self, menu: Vec<MenuItem>) -> Result<()> {
+        use gtk::prelude::{ContainerExt, WidgetExt};
+
+        self.menu = menu;
+        for child in self.gtk_menu.get_children() {
+            self.gtk_menu.remove(&child);
+        }
+
+        let mut widgets = HashMap::new();
+        let context = menu::GtkMenuContext {
+            source: menu::MenuSource::Tray,
+            accel_group: None,
+            event_proxy: &self.event_proxy,
+            updating: &self.menu_updating,
+        };
+        menu::append_gtk_items(&self.gtk_menu, &self.menu, &context, &mut widgets);
+        self.menu_widgets = widgets;
+        self.gtk_menu.show_all();
+        Ok(())
+    }
+
+    /// Changes the title, state or check mark of a menu item.
+    #[cfg(target_os = "linux")]
+    pub fn update_menu_item(&mut self, id: &str, update: &MenuItemUpdate) -> Result<()> {
+        let item = menu::find_item_mut(&mut self.menu, id).ok_or_else(|| Error::TrayError {
+            detail: format!("No tray menu item with id `{}`", id),
+        })?;
+        item.update(update);
+
+        if let Some(widget) = self.menu_widgets.get(id) {
+            menu::update_gtk_item(widget, item, &self.menu_updating);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn set_icon(&mut self, _icon: &Path) -> Result<()> {
+        Err(unsupported())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn set_tooltip(&mut self, _tooltip: Option<&str>) -> Result<()> {
+        Err(unsupported())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn set_visible(&mut self, _visible: bool) -> Result<()> {
+        Err(unsupported())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn set_menu(&mut self, _menu: Vec<MenuItem>) -> Result<()> {
+        Err(unsupported())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn update_menu_item(&mut self, _id: &str, _update: &MenuItemUpdate) -> Result<()> {
+        Err(unsupported())
+    }
+
+    /// Runs an action, icons are resolved relative to `asset_dir` and must be inside of it.
+    pub fn apply(&mut self, action: TrayAction, asset_dir: &str) -> Result<()> {
+        match action {
+            TrayAction::SetIcon(icon) => self.set_icon(&assets::resolve(asset_dir, &icon)?),
+            TrayAction::SetTooltip(tooltip) => self.set_tooltip(tooltip.as_deref()),
+            TrayAction::SetVisible(visible) => self.set_visible(visible),
+            TrayAction::SetMenu(menu) => self.set_menu(menu),
+            TrayAction::UpdateMenuItem { id, update } => self.update_menu_item(&id, &update),
+        }
+    }
+
+    /// Remembers the state of a check item which the user toggled.
+    pub fn set_checked(&mut self, id: &str, checked: bool) {
+        if let Some(item) = menu::find_item_mut(&mut self.menu, id) {
+            item.checked = checked;
+        }
+    }
+
+    /// Returns the id of the item which was activated through a tao menu event and the
+    /// new state of a check item. Disabled items return `None`.
+    #[cfg(not(target_os = "linux"))]
+    pub fn menu_activated(&mut self, menu_id: u32) -> Option<(String, Option<bool>)> {
+        let id = self.menu_ids.get(&menu_id)?.clone();
+        let item = menu::find_item_mut(&mut self.menu, &id)?;
+
+        if item.kind == menu::MenuItemKind::Check {
+            item.checked = !item.checked;
+            Some((id, Some(item.checked)))
+        } else {
+            Some((id, None))
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn status_icon_ptr(&self) -> *mut gtk_sys::GtkStatusIcon {
+        use glib::ObjectType;
+
+        self.status_icon.as_ptr() as *mut gtk_sys::GtkStatusIcon
+    }
+}
+
+/// Places the popup menu next to the tray icon which is passed as `icon`.
+#[cfg(target_os = "linux")]
+unsafe extern "C" fn position_menu(
+    menu: *mut gtk_sys::GtkMenu,
+    x: *mut std::os::raw::c_int,
+    y: *mut std::os::raw::c_int,
+    push_in: *mut glib::glib_sys::gboolean,
+    icon: glib::glib_sys::gpointer,
+) {
+    gtk_sys::gtk_status_icon_position_menu(menu, x, y, push_in, icon as *mut _);
+}
+
+#[cfg(target_os = "linux")]
+fn send_event(event_proxy: &EventLoopProxy<Event>, event: TrayEvent) {
+    use crate::events::VeloxEvents;
+
+    if let Err(err) = event_proxy.send_event(Event::VeloxEvent(VeloxEvents::TrayEvent(event))) {
+        log::error!("{}", err);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn c_string(text: &str) -> Result<std::ffi::CString> {
+    std::ffi::CString::new(text).map_err(tray_error)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unsupported() -> Error {
+    Error::TrayError {
+        detail: "The tray can't be changed on this platform".to_string(),
+    }
+}
+
+fn tray_error<E: std::fmt::Display>(err: E) -> Error {
+    Error::TrayError {
+        detail: err.to_string(),
+    }
+}
@@ -0,0 +1,429 @@
+//! Updates a packaged app to the newest release.
+//!
+//! The updater fetches a signed manifest from the configured endpoint. `manifest` is
+//! the manifest as JSON string and `signature` the base64 ed25519 signature of it:
+//!
+//! ```json
+//! {
+//!     "manifest": "{\"version\":\"0.2.0\",\"notes\":\"Bug fixes\",\"url\":\"https://example.com/releases/my-app-0.2.0.tar.gz\",\"signature\":\"...\"}",
+//!     "signature": "<base64 ed25519 signature of the manifest string>"
+//! }
+//! ```
+//!
+//! Nothing of the manifest is used before its signature is verified with the configured
+//! public key. If its version is newer than the version of the app, the archive at `url`
+//! is downloaded and its `signature` is verified with the same key. The archive is a
+//! `.tar.gz` file which contains the new executable, named like the executable of the
+//! app, and optionally the new assets in `dist/`:
+//!
+//! ```text
+//! my-app
+//! dist/index.html
+//! dist/...
+//! ```
+//!
+//! The executable and the asset directory are replaced together. Only `http` and
+//! `https` urls are supported, downloads time out and are limited in size.
+
+use crate::config::UpdaterConfig;
+use crate::{Error, Result};
+
+use std::convert::TryInto;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+/// Redirects which are followed before a download fails
+const MAX_REDIRECTS: u32 = 5;
+/// Largest manifest which is accepted
+const MAX_MANIFEST_SIZE: u64 = 64 * 1024;
+/// Largest archive which is downloaded
+const MAX_ARCHIVE_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Describes the newest release of an app.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Manifest {
+    pub version: String,
+    /// Release notes which can be shown to the user
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Url of the `.tar.gz` archive of the release
+    pub url: String,
+    /// Base64 encoded ed25519 signature of the archive
+    pub signature: String,
+}
+
+/// Response of the endpoint, the manifest is kept as string until it was verified.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignedManifest {
+    /// JSON of a `Manifest`
+    pub manifest: String,
+    /// Base64 encoded ed25519 signature of `manifest`
+    pub signature: String,
+}
+
+pub struct Updater {
+    endpoint: String,
+    /// Base64 encoded ed25519 public key
+    pubkey: String,
+    current_version: String,
+    /// File which is replaced by the executable of the update
+    target: PathBuf,
+    /// Directory which is replaced by the assets of the update
+    asset_dir: Option<PathBuf>,
+    agent: ureq::Agent,
+}
+
+impl Updater {
+    /// Creates an updater which replaces the executable of this process.
+    pub fn new(config: &UpdaterConfig, current_version: &str) -> Result<Self> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout_read(Duration::from_secs(30))
+            .redirects(MAX_REDIRECTS)
+            .user_agent("velox-updater")
+            .build();
+
+        Ok(Self {
+            endpoint: config.endpoint.clone(),
+            pubkey: config.pubkey.clone(),
+            current_version: current_version.to_string(),
+            target: std::env::current_exe()?,
+            asset_dir: None,
+            agent,
+        })
+    }
+
+    /// Replaces another file instead of the executable, for example in tests.
+    pub fn with_target(mut self, target: PathBuf) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Replaces `asset_dir` with the assets of an update.
+    pub fn with_asset_dir(mut self, asset_dir: PathBuf) -> Self {
+        self.asset_dir = Some(asset_dir);
+        self
+    }
+
+    /// File which is replaced by the update.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Fetches and verifies the manifest and returns it if it describes a newer version.
+    pub fn check(&self) -> Result<Option<Manifest>> {
+        let body = http_get(
+            &self.agent,
+            &self.endpoint,
+            MAX_MANIFEST_SIZE,
+            &mut |_, _| {},
+        )?;
+        let signed: SignedManifest = serde_json::from_slice(&body)?;
+
+        self.verify(signed.manifest.as_bytes(), &signed.signature)?;
+        let manifest: Manifest = serde_json::from_str(&signed.manifest)?;
+
+        if is_newer(&manifest.version, &self.current_version) {
+            Ok(Some(manifest))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Downloads the archive of a release which is newer than the app, `progress`
+    /// receives the downloaded and the total number of bytes if the server sent it.
+    pub fn download<F: FnMut(u64, Option<u64>)>(
+        &self,
+        manifest: &Manifest,
+        mut progress: F,
+    ) -> Result<Vec<u8>> {
+        if !is_newer(&manifest.version, &self.current_version) {
+            return Err(Error::UpdaterError {
+                detail: format!(
+                    "version {} is not newer than {}",
+                    manifest.version, self.current_version
+                ),
+            });
+        }
+
+        http_get(&self.agent, &manifest.url, MAX_ARCHIVE_SIZE, &mut progress)
+    }
+
+    /// Checks that `data` was signed by the owner of the configured public key.
+    pub fn verify(&self, data: &[u8], signature: &str) -> Result<()> {
+        let pubkey = base64::decode(self.pubkey.trim()).map_err(update_error)?;
+        let pubkey: [u8; 32] = pubkey
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::UpdaterError {
+                detail: "public key of the updater must be 32 bytes long".to_string(),
+            })?;
+        let pubkey = VerifyingKey::from_bytes(&pubkey).map_err(update_error)?;
+
+        let signature = base64::decode(signature.trim()).map_err(update_error)?;
+        let signature = Signature::from_slice(&signature).map_err(update_error)?;
+
+        pubkey
+            .verify_strict(data, &signature)
+            .map_err(|_| Error::UpdaterError {
+                detail: "signature of the update is invalid".to_string(),
+            })
+    }
+
+    /// Replaces the executable and the assets with the contents of a verified archive.
+    /// Both are unpacked next to what they replace and renamed over it, so neither is
+    /// left half written. The assets are restored if the executable can't be replaced.
+    pub fn install(&self, archive: &[u8]) -> Result<()> {
+        let staged = self.unpack(archive);
+        let res = staged.and_then(|staged| self.swap(&staged));
+
+        let _ = fs::remove_file(staging_path(&self.target, "update"));
+        if let Some(asset_dir) = &self.asset_dir {
+            let _ = fs::remove_dir_all(staging_path(asset_dir, "update"));
+            let _ = fs::remove_dir_all(staging_path(asset_dir, "old"));
+        }
+        res
+    }
+
+    /// Unpacks the executable and the assets of an archive next to their targets.
+    fn unpack(&self, archive: &[u8]) -> Result<Staged> {
+        let exe_name = self.target.file_name().unwrap_or_default().to_os_string();
+        let staged_exe = staging_path(&self.target, "update");
+        let staged_assets = self
+            .asset_dir
+            .as_ref()
+            .map(|asset_dir| staging_path(asset_dir, "update"));
+
+        let _ = fs::remove_file(&staged_exe);
+        if let Some(staged_assets) = &staged_assets {
+            let _ = fs::remove_dir_all(staged_assets);
+            fs::create_dir_all(staged_assets)?;
+        }
+
+        let mut has_exe = false;
+        let mut has_assets = false;
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            let path = path.strip_prefix(".").unwrap_or(&path);
+            let kind = entry.header().entry_type();
+
+            if path.as_os_str().is_empty() && kind.is_dir() {
+                continue;
+            } else if path.as_os_str() == exe_name && kind.is_file() {
+                entry.unpack(&staged_exe)?;
+                has_exe = true;
+            } else if path.starts_with("dist") && (kind.is_file() || kind.is_dir()) {
+                let staged_assets = staged_assets.as_ref().ok_or_else(|| Error::UpdaterError {
+                    detail: "update contains assets, but the app has no asset directory"
+                        .to_string(),
+                })?;
+                // refuses paths which lead out of the staging directory
+                if !entry.unpack_in(staged_assets)? {
+                    return Err(update_error(format!("invalid path in update: {:?}", path)));
+                }
+                has_assets = true;
+            } else {
+                return Err(update_error(format!(
+                    "unexpected file in update: {:?}",
+                    path
+                )));
+            }
+        }
+
+        if !has_exe {
+            return Err(update_error(format!(
+                "update does not contain the executable {:?}",
+                exe_name
+            )));
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&staged_exe, fs::Permissions::from_mode(0o755))?;
+        }
+
+        Ok(Staged {
+            exe: staged_exe,
+            assets: staged_assets
+                .filter(|_| has_assets)
+                .map(|staged| staged.join("dist")),
+        })
+    }
+
+    /// Renames the staged assets and executable over the ones of the app.
+    fn swap(&self, staged: &Staged) -> Result<()> {
+        let assets = match (&staged.assets, &self.asset_dir) {
+            (Some(staged_assets), Some(asset_dir)) => {
+                // windows apps keep their assets next to the running executable
+                if self.target.starts_with(asset_dir) {
+                    return Err(Error::UpdaterError {
+                        detail: "assets can't be replaced, they contain the executable".to_string(),
+                    });
+                }
+
+                let old = staging_path(asset_dir, "old");
+                let _ = fs::remove_dir_all(&old);
+                let existed = asset_dir.exists();
+                if existed {
+                    fs::rename(asset_dir, &old)?;
+                }
+                if let Err(err) = fs::rename(staged_assets, asset_dir) {
+                    if existed {
+                        let _ = fs::rename(&old, asset_dir);
+                    }
+                    return Err(err.into());
+                }
+                Some((asset_dir, old, existed))
+            }
+            _ => None,
+        };
+
+        if let Err(err) = replace_executable(&staged.exe, &self.target) {
+            if let Some((asset_dir, old, existed)) = assets {
+                let _ = fs::remove_dir_all(asset_dir);
+                if existed {
+                    let _ = fs::rename(&old, asset_dir);
+                }
+            }
+            return Err(err.into());
+        }
+        Ok(())
+    }
+}
+
+/// Files of an update which were unpacked next to their targets.
+struct Staged {
+    exe: PathBuf,
+    assets: Option<PathBuf>,
+}
+
+/// Path next to `path` where its replacement is prepared.
+fn staging_path(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}", name, suffix))
+}
+
+/// Renames `new` over the executable `target`.
+fn replace_executable(new: &Path, target: &Path) -> std::io::Result<()> {
+    // windows can't replace a running executable, but it can rename it
+    #[cfg(windows)]
+    {
+        let name = target.file_name().unwrap_or_default().to_string_lossy();
+        let old = target.with_file_name(format!("{}.old", name));
+        let _ = fs::remove_file(&old);
+        fs::rename(target, &old)?;
+    }
+
+    fs::rename(new, target)
+}
+
+/// Compares dotted version numbers like `1.2.10` and `1.2.9`.
+/// Pre-release suffixes like `-beta` are ignored.
+pub fn is_newer(version: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+
+    let (mut version, mut current) = (parts(version), parts(current));
+    let len = version.len().max(current.len());
+    version.resize(len, 0);
+    current.resize(len, 0);
+
+    version > current
+}
+
+fn update_error<E: ToString>(err: E) -> Error {
+    Error::UpdaterError {
+        detail: err.to_string(),
+    }
+}
+
+/// Downloads a file with a GET request and fails if it is larger than `limit`.
+fn http_get(
+    agent: &ureq::Agent,
+    url: &str,
+    limit: u64,
+    progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(Error::UpdaterError {
+            detail: format!("unsupported url: {}", url),
+        });
+    }
+
+    let response = agent.get(url).call().map_err(update_error)?;
+    let total: Option<u64> = response
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok());
+    let too_large = || Error::UpdaterError {
+        detail: format!("{} is larger than {} bytes", url, limit),
+    };
+    if total.is_some_and(|total| total > limit) {
+        return Err(too_large());
+    }
+
+    let mut reader = response.into_reader().take(limit + 1);
+    let mut body = Vec::new();
+    let mut buf = [0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&buf[..read]);
+        if body.len() as u64 > limit {
+            return Err(too_large());
+        }
+        progress(body.len() as u64, total);
+    }
+
+    if total.is_some_and(|total| (body.len() as u64) < total) {
+        return Err(Error::UpdaterError {
+            detail: format!("download of {} was interrupted", url),
+        });
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_versions() {
+        assert!(is_newer("0.2.0", "0.1.0"));
+        assert!(is_newer("1.2.10", "1.2.9"));
+        assert!(is_newer("v1.0.1", "1.0.0"));
+        assert!(is_newer("1.1", "1.0.9"));
+    }
+
+    #[test]
+    fn older_or_equal_versions() {
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0", "0.2.0"));
+        assert!(!is_newer("1.0", "1.0.0"));
+        assert!(!is_newer("1.0.0-beta", "1.0.0"));
+        assert!(!is_newer("1.0.0+build.5", "1.0.0"));
+        assert!(!is_newer("invalid", "0.0.1"));
+    }
+}
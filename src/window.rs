@@ -1,4 +1,19 @@
-use wry::{application::window::WindowId, webview::WebView};
+use crate::events::{Event, Reply, WindowEvents};
+use crate::menu::{self, MenuItem, MenuItemUpdate};
+use crate::{Error, Result};
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wry::{
+    application::{
+        dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+        event_loop::EventLoopProxy,
+        monitor::MonitorHandle,
+        window::{Icon, WindowBuilder, WindowId},
+    },
+    webview::WebView,
+};
 
 /// Describes a webview window
 pub struct WebviewWindow {
@@ -8,9 +23,164 @@ pub struct WebviewWindow {
     pub window_id: WindowId,
     // Instance of webview which also holds a reference to a tao window
     pub webview: WebView,
+    /// Whether javascript listens for close requests of this window and decides itself
+    /// if the window should be closed.
+    pub intercept_close: bool,
+    /// Whether the window is visible
+    pub visible: bool,
+    /// Whether the window is minimized
+    pub minimized: bool,
+    /// Whether the window has focus
+    pub focused: bool,
+    /// Identifier of the window which owns this window
+    pub parent: Option<String>,
+    /// Whether input to the parent is disabled while this window is open
+    pub modal: bool,
+    /// Result of the window which is returned to the opener of a modal window
+    pub result: wry::Value,
+    /// Where the result is delivered to when a modal window closes
+    pub modal_reply: Option<Reply>,
+    /// Zoom factor of the page
+    pub zoom: f64,
+    /// Replies of scripts whose result is still pending, by token
+    pub pending_evals: HashMap<String, Reply>,
+    /// Menu bar of the window
+    pub menu: Vec<MenuItem>,
+    /// Widgets of the menu items by id
+    #[cfg(target_os = "linux")]
+    menu_widgets: HashMap<String, gtk::MenuItem>,
+    /// Set while items are changed from rust, which must not emit activations
+    #[cfg(target_os = "linux")]
+    menu_updating: std::rc::Rc<std::cell::Cell<bool>>,
+    #[cfg(target_os = "linux")]
+    accel_group: Option<gtk::AccelGroup>,
+    /// Ids of the menu items by the id of their tao menu item
+    #[cfg(not(target_os = "linux"))]
+    menu_ids: HashMap<u32, String>,
+}
+
+/// Options for opening a new window.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowOptions {
+    /// Identifier of the window which owns the new window.
+    /// The new window stays above its parent and closes with it.
+    pub parent: Option<String>,
+    /// Disables input to the parent until the new window is closed
+    pub modal: bool,
+    /// Icon of the new window, a PNG or ICO file in the asset directory
+    pub icon: Option<String>,
+    /// Width of the new window in logical pixels
+    pub width: Option<f64>,
+    /// Height of the new window in logical pixels
+    pub height: Option<f64>,
+    /// Whether the new window has a titlebar and borders, defaults to `true`
+    pub decorations: Option<bool>,
+    /// Menu bar of the new window
+    pub menu: Option<Vec<MenuItem>>,
+}
+
+impl WindowOptions {
+    /// Applies the size and decorations of these options to a window builder.
+    pub fn apply(&self, mut window_builder: WindowBuilder) -> WindowBuilder {
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            window_builder = window_builder.with_inner_size(LogicalSize::new(width, height));
+        }
+        if let Some(decorations) = self.decorations {
+            window_builder = window_builder.with_decorations(decorations);
+        }
+        window_builder
+    }
+}
+
+/// Edge or corner of a window which is dragged to resize it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResizeDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+/// State of a webview window which is sent to javascript.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    pub identifier: String,
+    /// Size of the client area of the window
+    pub inner_size: PhysicalSize<u32>,
+    /// Size of the entire window including decorations
+    pub outer_size: PhysicalSize<u32>,
+    /// Position of the top left corner of the window, if supported by the platform
+    pub position: Option<PhysicalPosition<i32>>,
+    pub scale_factor: f64,
+    pub maximized: bool,
+    pub minimized: bool,
+    pub fullscreen: bool,
+    pub visible: bool,
+    pub focused: bool,
+    /// Zoom factor of the page
+    pub zoom: f64,
+}
+
+/// Describes a monitor which is connected to the system.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    /// Human-readable name of the monitor
+    pub name: Option<String>,
+    /// Resolution of the monitor
+    pub size: PhysicalSize<u32>,
+    /// Position of the top left corner of the monitor
+    pub position: PhysicalPosition<i32>,
+    pub scale_factor: f64,
+}
+
+impl From<&MonitorHandle> for MonitorInfo {
+    fn from(monitor: &MonitorHandle) -> Self {
+        Self {
+            name: monitor.name(),
+            size: monitor.size(),
+            position: monitor.position(),
+            scale_factor: monitor.scale_factor(),
+        }
+    }
 }
 
 impl WebviewWindow {
+    /// `visible` is the visibility which the window was built with.
+    pub fn new(identifier: String, webview: WebView, visible: bool) -> Self {
+        Self {
+            identifier,
+            window_id: webview.window().id(),
+            webview,
+            intercept_close: false,
+            visible,
+            minimized: false,
+            focused: false,
+            parent: None,
+            modal: false,
+            result: wry::Value::Null,
+            modal_reply: None,
+            zoom: 1.0,
+            pending_evals: HashMap::new(),
+            menu: vec![],
+            #[cfg(target_os = "linux")]
+            menu_widgets: HashMap::new(),
+            #[cfg(target_os = "linux")]
+            menu_updating: Default::default(),
+            #[cfg(target_os = "linux")]
+            accel_group: None,
+            #[cfg(not(target_os = "linux"))]
+            menu_ids: HashMap::new(),
+        }
+    }
+
     pub fn fullscreen(&self) {
         self.webview.window().fullscreen();
     }
@@ -20,7 +190,466 @@ impl WebviewWindow {
     pub fn maximize(&self, flag: bool) {
         self.webview.window().set_maximized(flag);
     }
-    pub fn minimize(&self, flag: bool) {
+    pub fn minimize(&mut self, flag: bool) {
         self.webview.window().set_minimized(flag);
+        self.minimized = flag;
+    }
+    pub fn set_visible(&mut self, flag: bool) {
+        self.webview.window().set_visible(flag);
+        self.visible = flag;
+    }
+    /// Shows, restores and focuses the window.
+    pub fn focus(&mut self) {
+        self.set_visible(true);
+        self.minimize(false);
+        self.webview.window().set_focus();
+    }
+    pub fn set_icon(&self, icon: Icon) {
+        self.webview.window().set_window_icon(Some(icon));
+    }
+
+    /// Keeps the window above its parent window.
+    #[allow(unused_variables)]
+    pub fn set_parent(&self, parent: &WebviewWindow) {
+        #[cfg(target_os = "linux")]
+        {
+            use gtk::GtkWindowExt;
+            use wry::application::platform::unix::WindowExtUnix;
+
+            self.webview
+                .window()
+                .gtk_window()
+                .set_transient_for(Some(parent.webview.window().gtk_window()));
+        }
+
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use winapi::um::winuser::{SetWindowLongPtrW, GWLP_HWNDPARENT};
+            use wry::application::platform::windows::WindowExtWindows;
+
+            SetWindowLongPtrW(
+                self.webview.window().hwnd() as _,
+                GWLP_HWNDPARENT,
+                parent.webview.window().hwnd() as _,
+            );
+        }
+    }
+
+    /// Enables or disables user input to the window.
+    #[allow(unused_variables)]
+    pub fn set_enabled(&self, flag: bool) {
+        #[cfg(target_os = "linux")]
+        {
+            use gtk::WidgetExt;
+            use wry::application::platform::unix::WindowExtUnix;
+
+            self.webview.window().gtk_window().set_sensitive(flag);
+        }
+
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use winapi::um::winuser::EnableWindow;
+            use wry::application::platform::windows::WindowExtWindows;
+
+            EnableWindow(self.webview.window().hwnd() as _, flag as i32);
+        }
+    }
+
+    /// Moves the window with the mouse until the left mouse button is released.
+    pub fn start_drag(&self) -> Result<()> {
+        self.webview
+            .window()
+            .drag_window()
+            .map_err(|err| Error::CommandError {
+                detail: err.to_string(),
+            })
+    }
+
+    /// Resizes the window with the mouse until the left mouse button is released.
+    #[allow(unused_variables)]
+    pub fn start_resize(&self, direction: ResizeDirection) {
+        #[cfg(target_os = "linux")]
+        {
+            use gdk::WindowEdge;
+            use gtk::{GtkWindowExt, WidgetExt};
+            use wry::application::platform::unix::WindowExtUnix;
+
+            let edge = match direction {
+                ResizeDirection::North => WindowEdge::North,
+                ResizeDirection::NorthEast => WindowEdge::NorthEast,
+                ResizeDirection::East => WindowEdge::East,
+                ResizeDirection::SouthEast => WindowEdge::SouthEast,
+                ResizeDirection::South => WindowEdge::South,
+                ResizeDirection::SouthWest => WindowEdge::SouthWest,
+                ResizeDirection::West => WindowEdge::West,
+                ResizeDirection::NorthWest => WindowEdge::NorthWest,
+            };
+
+            let window = self.webview.window().gtk_window();
+            if let Some(pointer) = window
+                .get_display()
+                .get_device_manager()
+                .and_then(|manager| manager.get_client_pointer())
+            {
+                let (_, x, y) = pointer.get_position();
+                window.begin_resize_drag(edge, 1, x, y, 0);
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use winapi::shared::windef::POINT;
+            use winapi::um::winuser::{
+                GetCursorPos, PostMessageW, ReleaseCapture, HTBOTTOM, HTBOTTOMLEFT, HTBOTTOMRIGHT,
+                HTLEFT, HTRIGHT, HTTOP, HTTOPLEFT, HTTOPRIGHT, WM_NCLBUTTONDOWN,
+            };
+            use wry::application::platform::windows::WindowExtWindows;
+
+            let hit_test = match direction {
+                ResizeDirection::North => HTTOP,
+                ResizeDirection::NorthEast => HTTOPRIGHT,
+                ResizeDirection::East => HTRIGHT,
+                ResizeDirection::SouthEast => HTBOTTOMRIGHT,
+                ResizeDirection::South => HTBOTTOM,
+                ResizeDirection::SouthWest => HTBOTTOMLEFT,
+                ResizeDirection::West => HTLEFT,
+                ResizeDirection::NorthWest => HTTOPLEFT,
+            };
+
+            let mut point: POINT = std::mem::zeroed();
+            GetCursorPos(&mut point);
+            let position = (point.x as u16 as u32) | ((point.y as u16 as u32) << 16);
+
+            ReleaseCapture();
+            PostMessageW(
+                self.webview.window().hwnd() as _,
+                WM_NCLBUTTONDOWN,
+                hit_test as _,
+                position as _,
+            );
+        }
+    }
+
+    /// Returns the webkit webview of the window.
+    #[cfg(target_os = "linux")]
+    fn webkit_webview(&self) -> Option<webkit2gtk::WebView> {
+        use gtk::prelude::{Cast, ContainerExt};
+        use wry::application::platform::unix::WindowExtUnix;
+
+        let mut widgets = self.webview.window().gtk_window().get_children();
+        while let Some(widget) = widgets.pop() {
+            match widget.downcast::<webkit2gtk::WebView>() {
+                Ok(webview) => return Some(webview),
+                Err(widget) => {
+                    if let Ok(container) = widget.downcast::<gtk::Container>() {
+                        widgets.extend(container.get_children());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Enables or disables the web inspector of the window.
+    /// Only supported on linux, other platforms enable it in debug builds.
+    #[allow(unused_variables)]
+    pub fn set_devtools(&self, flag: bool) {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::{SettingsExt, WebViewExt};
+
+            if let Some(settings) = self.webkit_webview().and_then(|w| w.get_settings()) {
+                settings.set_enable_developer_extras(flag);
+            }
+        }
+    }
+
+    /// Sends `WindowEvents::LoadFailed` when a page of the window can't be loaded.
+    /// Only supported on linux, other platforms rely on the timeouts of their callers.
+    #[allow(unused_variables)]
+    pub fn watch_load_failures(&self, event_proxy: EventLoopProxy<Event>) {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::WebViewExt;
+
+            if let Some(webview) = self.webkit_webview() {
+                let identifier = self.identifier.clone();
+                webview.connect_load_failed(move |_, _, uri, err| {
+                    // loads which were replaced by another navigation did not fail
+                    if !matches!(err.kind(), Some(webkit2gtk::NetworkError::Cancelled)) {
+                        log::warn!("[{}] could not load {}: {}", identifier, uri, err);
+                        let event = WindowEvents::LoadFailed(identifier.clone());
+                        if let Err(err) = event_proxy.send_event(Event::WindowEvent(event)) {
+                            log::error!("{}", err);
+                        }
+                    }
+                    false
+                });
+            }
+        }
+    }
+
+    /// Opens the web inspector of the window.
+    /// Only supported on linux.
+    pub fn open_devtools(&self) {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::{WebInspectorExt, WebViewExt};
+
+            if let Some(inspector) = self.webkit_webview().and_then(|w| w.get_inspector()) {
+                inspector.show();
+            }
+        }
+    }
+
+    /// Reloads the page of the window.
+    pub fn reload(&self) -> Result<()> {
+        self.eval("window.location.reload()")
+    }
+
+    /// Loads a url in the window.
+    pub fn navigate(&self, url: &str) -> Result<()> {
+        self.eval(&format!("window.location.href = {}", wry::Value::from(url)))
+    }
+
+    /// Goes back in the history of the window.
+    pub fn go_back(&self) -> Result<()> {
+        self.eval("window.history.back()")
+    }
+
+    /// Goes forward in the history of the window.
+    pub fn go_forward(&self) -> Result<()> {
+        self.eval("window.history.forward()")
+    }
+
+    /// Sets the zoom factor of the page and tells javascript about it.
+    /// Linux zooms the webview itself, other platforms fall back to css zoom.
+    pub fn set_zoom(&mut self, factor: f64) -> Result<()> {
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(Error::CommandError {
+                detail: format!("Invalid zoom factor {}", factor),
+            });
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::WebViewExt;
+
+            if let Some(webview) = self.webkit_webview() {
+                webview.set_zoom_level(factor);
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        self.eval(&format!("document.documentElement.style.zoom = {}", factor))?;
+
+        self.zoom = factor;
+        self.emit("zoom-changed", wry::Value::from(factor))
+    }
+
+    /// Moves the window to the center of a monitor.
+    pub fn set_monitor(&self, monitor: &MonitorHandle) {
+        let window = self.webview.window();
+        let size = window.outer_size();
+        let x = monitor.position().x + (monitor.size().width as i32 - size.width as i32) / 2;
+        let y = monitor.position().y + (monitor.size().height as i32 - size.height as i32) / 2;
+        window.set_outer_position(PhysicalPosition::new(x, y));
+    }
+
+    /// Returns the current state of the window.
+    pub fn info(&self) -> WindowInfo {
+        let window = self.webview.window();
+        WindowInfo {
+            identifier: self.identifier.clone(),
+            inner_size: window.inner_size(),
+            outer_size: window.outer_size(),
+            position: window.outer_position().ok(),
+            scale_factor: window.scale_factor(),
+            maximized: window.is_maximized(),
+            minimized: self.minimized,
+            fullscreen: window.fullscreen().is_some(),
+            visible: self.visible,
+            focused: self.focused,
+            zoom: self.zoom,
+        }
+    }
+
+    /// Evaluates a script inside of the webview.
+    /// Must be called from the thread which runs the event loop.
+    pub fn eval(&self, script: &str) -> Result<()> {
+        self.webview.dispatcher().dispatch_script(script)?;
+        self.webview.evaluate_script()?;
+        Ok(())
+    }
+
+    /// Evaluates a script and sends its JSON-serialized result back to velox as `eval_result`.
+    /// The result is delivered to `reply` once javascript answered with the random token
+    /// of the script.
+    pub fn eval_with_result(&mut self, script: &str, reply: Reply) -> Result<()> {
+        let token = format!("{:032x}", rand::random::<u128>());
+        self.eval(&format!(
+            r#"
+            (async function () {{
+                let success = true;
+                let value;
+                try {{
+                    value = await (0, eval)({script});
+                    value = value === undefined ? null : JSON.parse(JSON.stringify(value));
+                }} catch (err) {{
+                    success = false;
+                    value = String(err);
+                }}
+                window.__VELOX__.rpc.call("eval_result", {token}, success, value);
+            }})()
+            "#,
+            script = wry::Value::from(script),
+            token = wry::Value::from(token.as_str()),
+        ))?;
+        self.pending_evals.insert(token, reply);
+        Ok(())
+    }
+
+    /// Emits an event to the javascript listeners of this window.
+    pub fn emit(&self, event: &str, payload: wry::Value) -> Result<()> {
+        self.eval(&format!(
+            "window.__VELOX__.event._dispatch({}, {})",
+            wry::Value::from(event),
+            payload
+        ))
+    }
+
+    /// Replaces the menu bar of the window, an empty menu removes it.
+    /// Fails if the menu contains other items than submenus.
+    /// `event_proxy` receives the activations of the items.
+    #[allow(unused_variables)]
+    pub fn set_menu(
+        &mut self,
+        menu: Vec<MenuItem>,
+        event_proxy: EventLoopProxy<Event>,
+    ) -> Result<()> {
+        menu::check_menu_bar(&menu)?;
+        self.menu = menu;
+
+        #[cfg(target_os = "linux")]
+        self.build_gtk_menu(event_proxy);
+
+        #[cfg(not(target_os = "linux"))]
+        self.build_tao_menu();
+
+        Ok(())
+    }
+
+    /// Changes the title, state or check mark of a menu item.
+    pub fn update_menu_item(&mut self, id: &str, update: &MenuItemUpdate) -> Result<()> {
+        let item = menu::find_item_mut(&mut self.menu, id).ok_or_else(|| Error::CommandError {
+            detail: format!("No menu item with id `{}`", id),
+        })?;
+        item.update(update);
+
+        #[cfg(target_os = "linux")]
+        if let Some(widget) = self.menu_widgets.get(id) {
+            menu::update_gtk_item(widget, item, &self.menu_updating);
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        self.build_tao_menu();
+
+        Ok(())
+    }
+
+    /// Builds the menu bar with gtk, which supports everything that `MenuItem` describes.
+    /// The webview is moved into a box below the menu bar if it has none yet.
+    #[cfg(target_os = "linux")]
+    fn build_gtk_menu(&mut self, event_proxy: EventLoopProxy<Event>) {
+        use gtk::prelude::*;
+        use wry::application::platform::unix::WindowExtUnix;
+
+        let gtk_window = self.webview.window().gtk_window();
+
+        // tao packs the webview into a box if the window was built with a menu
+        let vbox = match gtk_window.get_children().pop() {
+            Some(widget) => match widget.downcast::<gtk::Box>() {
+                Ok(vbox) => vbox,
+                Err(webview) => {
+                    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
+                    gtk_window.remove(&webview);
+                    vbox.pack_start(&webview, true, true, 0);
+                    gtk_window.add(&vbox);
+                    vbox
+                }
+            },
+            None => return,
+        };
+        for child in vbox.get_children() {
+            if child.is::<gtk::MenuBar>() {
+                vbox.remove(&child);
+            }
+        }
+
+        let accel_group = self.accel_group.get_or_insert_with(|| {
+            let accel_group = gtk::AccelGroup::new();
+            gtk_window.add_accel_group(&accel_group);
+            accel_group
+        });
+
+        let menubar = gtk::MenuBar::new();
+        let mut widgets = HashMap::new();
+        let context = menu::GtkMenuContext {
+            source: menu::MenuSource::Window(self.identifier.clone()),
+            accel_group: Some(accel_group),
+            event_proxy: &event_proxy,
+            updating: &self.menu_updating,
+        };
+        menu::append_gtk_items(&menubar, &self.menu, &context, &mut widgets);
+        self.menu_widgets = widgets;
+
+        if !self.menu.is_empty() {
+            vbox.pack_start(&menubar, false, false, 0);
+            vbox.reorder_child(&menubar, 0);
+        }
+        vbox.show_all();
+    }
+
+    /// Builds the menu bar with tao, nested submenus are flattened into their parent.
+    #[cfg(not(target_os = "linux"))]
+    fn build_tao_menu(&mut self) {
+        use wry::application::menu::Menu;
+
+        let source = menu::MenuSource::Window(self.identifier.clone());
+        menu::release_tao_items(&source);
+
+        let mut ids = HashMap::new();
+        let menus: Vec<Menu> = self
+            .menu
+            .iter()
+            .map(|submenu| {
+                Menu::new(
+                    &submenu.title,
+                    menu::tao_items(&submenu.items, "", &source, &mut ids),
+                )
+            })
+            .collect();
+
+        self.menu_ids = ids;
+        self.webview
+            .window()
+            .set_menu(if menus.is_empty() { None } else { Some(menus) });
+    }
+
+    /// Returns the id of the item which was activated through a tao menu event and the
+    /// new state of a check item. Disabled items and items of other windows return `None`.
+    #[cfg(not(target_os = "linux"))]
+    pub fn menu_activated(&mut self, menu_id: u32) -> Option<(String, Option<bool>)> {
+        let id = self.menu_ids.get(&menu_id)?.clone();
+        let item = menu::find_item_mut(&mut self.menu, &id)?;
+
+        if item.kind == menu::MenuItemKind::Check {
+            item.checked = !item.checked;
+            let checked = item.checked;
+            self.build_tao_menu();
+            Some((id, Some(checked)))
+        } else {
+            Some((id, None))
+        }
     }
 }
@@ -0,0 +1,188 @@
+//! Checks, downloads, verifies and installs updates from a local update server.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signer, SigningKey};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tiny_http::{Response, Server};
+use velox::config::UpdaterConfig;
+use velox::updater::Updater;
+
+/// Serves release `version` with `archive` until the test process exits.
+/// The manifest is signed with `manifest_key`, the archive with `archive_key`.
+/// Returns the url of the manifest.
+fn serve(
+    version: &str,
+    archive: Vec<u8>,
+    manifest_key: &SigningKey,
+    archive_key: &SigningKey,
+) -> String {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", server.server_addr());
+
+    let manifest = serde_json::json!({
+        "version": version,
+        "url": format!("{}/download", url),
+        "signature": base64::encode(archive_key.sign(&archive).to_bytes()),
+    })
+    .to_string();
+    let signed = serde_json::json!({
+        "signature": base64::encode(manifest_key.sign(manifest.as_bytes()).to_bytes()),
+        "manifest": manifest,
+    })
+    .to_string();
+
+    std::thread::spawn(move || {
+        for req in server.incoming_requests() {
+            let res = match req.url() {
+                "/manifest.json" => req.respond(Response::from_string(signed.clone())),
+                "/download" => req.respond(Response::from_data(archive.clone())),
+                _ => req.respond(Response::from_string("not found").with_status_code(404)),
+            };
+            res.unwrap();
+        }
+    });
+    format!("{}/manifest.json", url)
+}
+
+/// Packs `files` into a `.tar.gz` archive.
+fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, *data).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn key() -> SigningKey {
+    SigningKey::from_bytes(&rand::random())
+}
+
+/// Creates a directory with an installed app at version 0.1.0.
+fn installed_app(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("velox-updater-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("dist")).unwrap();
+    fs::write(dir.join("app"), b"old app").unwrap();
+    fs::write(dir.join("dist/index.html"), b"old page").unwrap();
+    dir
+}
+
+fn updater(endpoint: String, key: &SigningKey, dir: &Path) -> Updater {
+    let config = UpdaterConfig {
+        active: true,
+        endpoint,
+        pubkey: base64::encode(key.verifying_key().to_bytes()),
+        check_on_startup: false,
+    };
+
+    Updater::new(&config, "0.1.0")
+        .unwrap()
+        .with_target(dir.join("app"))
+        .with_asset_dir(dir.join("dist"))
+}
+
+#[test]
+fn installs_executable_and_assets() {
+    let dir = installed_app("install");
+    let key = key();
+    let archive = archive(&[
+        ("app", b"new app"),
+        ("dist/index.html", b"new page"),
+        ("dist/js/app.js", b"new script"),
+    ]);
+    let updater = updater(serve("0.2.0", archive, &key, &key), &key, &dir);
+
+    let manifest = updater.check().unwrap().expect("0.2.0 is newer than 0.1.0");
+    assert_eq!(manifest.version, "0.2.0");
+
+    let mut progress = vec![];
+    let archive = updater
+        .download(&manifest, |downloaded, total| {
+            progress.push((downloaded, total))
+        })
+        .unwrap();
+    assert_eq!(
+        progress.last(),
+        Some(&(archive.len() as u64, Some(archive.len() as u64)))
+    );
+
+    updater.verify(&archive, &manifest.signature).unwrap();
+    updater.install(&archive).unwrap();
+
+    assert_eq!(fs::read(dir.join("app")).unwrap(), b"new app");
+    assert_eq!(fs::read(dir.join("dist/index.html")).unwrap(), b"new page");
+    assert_eq!(fs::read(dir.join("dist/js/app.js")).unwrap(), b"new script");
+    // nothing of the update is left next to the app
+    let mut entries: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    entries.sort();
+    assert_eq!(entries, vec!["app", "dist"]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn ignores_releases_which_are_not_newer() {
+    let dir = installed_app("older");
+    let key = key();
+    let archive = archive(&[("app", b"old app")]);
+    let updater = updater(serve("0.1.0", archive, &key, &key), &key, &dir);
+
+    assert!(updater.check().unwrap().is_none());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn rejects_manifest_of_another_key() {
+    let dir = installed_app("manifest-key");
+    let (key, other) = (key(), key());
+    let archive = archive(&[("app", b"evil app")]);
+    let updater = updater(serve("0.2.0", archive, &other, &key), &key, &dir);
+
+    assert!(updater.check().is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn rejects_archive_of_another_key() {
+    let dir = installed_app("archive-key");
+    let (key, other) = (key(), key());
+    let archive = archive(&[("app", b"evil app")]);
+    let updater = updater(serve("0.2.0", archive, &key, &other), &key, &dir);
+
+    let manifest = updater.check().unwrap().unwrap();
+    let archive = updater.download(&manifest, |_, _| {}).unwrap();
+    assert!(updater.verify(&archive, &manifest.signature).is_err());
+    assert_eq!(fs::read(dir.join("app")).unwrap(), b"old app");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn rejects_archives_with_other_files() {
+    let dir = installed_app("other-files");
+    let key = key();
+    let updater = updater(serve("0.2.0", vec![], &key, &key), &key, &dir);
+
+    let unexpected = archive(&[("app", b"new app"), ("lib/evil.so", b"evil")]);
+    assert!(updater.install(&unexpected).is_err());
+    let without_app = archive(&[("dist/index.html", b"new page")]);
+    assert!(updater.install(&without_app).is_err());
+
+    assert_eq!(fs::read(dir.join("app")).unwrap(), b"old app");
+    assert_eq!(fs::read(dir.join("dist/index.html")).unwrap(), b"old page");
+    assert!(!dir.join("lib").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}